@@ -9,6 +9,31 @@ use syn::{
     token::Comma,
 };
 
+/// Arguments parsed from `#[context(...)]`, optionally prefixed with
+/// `scope`, e.g. `#[context(scope, "format string", arg1)]`.
+struct Args {
+    /// Set the context as ambient context while the function runs, rather
+    /// than adding it to the returned error.
+    scope: bool,
+    context: ContextArgs,
+}
+
+impl syn::parse::Parse for Args {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let scope = input.peek(syn::Ident) && input.fork().parse::<syn::Ident>()? == "scope";
+        if scope {
+            let _scope: syn::Ident = input.parse()?;
+            if !input.is_empty() {
+                let _comma: Token![,] = input.parse()?;
+            }
+        }
+        Ok(Args {
+            scope,
+            context: input.parse()?,
+        })
+    }
+}
+
 /// Arguments parsed from `#[context("format string", arg1, arg2, ...)]`
 /// or `#[context]` / `#[context()]` (auto-build from `#[fmt("...")]`
 /// parameter attributes).
@@ -135,9 +160,36 @@ fn extract_clone_ident(expr: &Expr) -> Option<&syn::Ident> {
 /// Both modes work with `async fn` and all receiver kinds (`self`, `&self`,
 /// `&mut self`). Two sibling items are emitted so that `self` in the body
 /// always refers to the real receiver — no aliasing required.
+///
+/// ## Scoped context
+///
+/// With `scope` first, e.g. `#[context(scope, "handling request {}", id)]`,
+/// the context is set as ambient context while the function runs, so it is
+/// attached to every `ErrorUnion` created inside it, including errors that
+/// never pass through the function's return. Async functions use
+/// `eros::ScopeFutureExt::in_error_scope_with`, so the context follows the
+/// task across awaits. The context is only formatted when an error is created
+/// inside the function, so as in the default mode, arguments moved into the
+/// call must be cloned with `.clone()`. Requires the `scope` feature of `eros`.
+///
+/// ```rust,ignore
+/// #[context(scope, "handling request {}", id)]
+/// async fn handle(id: u64) -> eros::Result<()> {
+///     // ...
+/// }
+/// ```
+///
+/// Expands to:
+///
+/// ```rust,ignore
+/// async fn handle(id: u64) -> eros::Result<()> {
+///     let context = |f: &mut fmt::Formatter<'_>| write!(f, "handling request {}", id);
+///     eros::ScopeFutureExt::in_error_scope_with(__handle_internal(id), context).await
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn context(attr: TokenStream, item: TokenStream) -> TokenStream {
-    let args = parse_macro_input!(attr as ContextArgs);
+    let args = parse_macro_input!(attr as Args);
     let func = parse_macro_input!(item as ItemFn);
 
     match expand_context(args, func) {
@@ -146,7 +198,11 @@ pub fn context(attr: TokenStream, item: TokenStream) -> TokenStream {
     }
 }
 
-fn expand_context(args: ContextArgs, func: ItemFn) -> syn::Result<TokenStream2> {
+fn expand_context(args: Args, func: ItemFn) -> syn::Result<TokenStream2> {
+    let Args {
+        scope,
+        context: args,
+    } = args;
     let is_async = func.sig.asyncness.is_some();
     let outer_name = &func.sig.ident;
     let inner_name = syn::Ident::new(&format!("__{}_internal", outer_name), outer_name.span());
@@ -228,7 +284,8 @@ fn expand_context(args: ContextArgs, func: ItemFn) -> syn::Result<TokenStream2>
         bare_ident: syn::Ident,
     }
 
-    let (clone_bindings, format_call): (Vec<CloneBinding>, TokenStream2) = match args {
+    // The format string and its arguments
+    let (clone_bindings, format_args): (Vec<CloneBinding>, TokenStream2) = match args {
         ContextArgs::Explicit {
             format_str,
             format_args,
@@ -258,13 +315,7 @@ fn expand_context(args: ContextArgs, func: ItemFn) -> syn::Result<TokenStream2>
                 })
                 .collect();
 
-            let fmt_call = if context_args.is_empty() {
-                quote! { eros::__private::format!(#format_str) }
-            } else {
-                quote! { eros::__private::format!(#format_str, #(#context_args),*) }
-            };
-
-            (bindings, fmt_call)
+            (bindings, quote! { #format_str #(, #context_args)* })
         }
 
         ContextArgs::Auto => {
@@ -286,7 +337,7 @@ fn expand_context(args: ContextArgs, func: ItemFn) -> syn::Result<TokenStream2>
             }
 
             let fmt_lit = syn::LitStr::new(&fmt_str, proc_macro2::Span::call_site());
-            (vec![], quote! { eros::__private::format!(#fmt_lit, #(#arg_idents),*) })
+            (vec![], quote! { #fmt_lit, #(#arg_idents),* })
         }
     };
 
@@ -327,7 +378,7 @@ fn expand_context(args: ContextArgs, func: ItemFn) -> syn::Result<TokenStream2>
     let awaited_call = if is_async {
         quote! { #raw_call.await }
     } else {
-        raw_call
+        raw_call.clone()
     };
 
     let clone_let_stmts: Vec<TokenStream2> = clone_bindings
@@ -339,6 +390,29 @@ fn expand_context(args: ContextArgs, func: ItemFn) -> syn::Result<TokenStream2>
         })
         .collect();
 
+    // Scoped context is only formatted when an error is created in the scope
+    let write_context = quote! {
+        |__eros_f: &mut ::core::fmt::Formatter<'_>| ::core::write!(__eros_f, #format_args)
+    };
+
+    let outer_body = if !scope {
+        quote! {
+            use eros::Context as _;
+            #(#clone_let_stmts)*
+            #awaited_call.with_context(|| eros::__private::format!(#format_args))
+        }
+    } else if is_async {
+        quote! {
+            #(#clone_let_stmts)*
+            eros::ScopeFutureExt::in_error_scope_with(#raw_call, #write_context).await
+        }
+    } else {
+        quote! {
+            #(#clone_let_stmts)*
+            eros::scope_with(#write_context, || #raw_call)
+        }
+    };
+
     Ok(quote! {
         #[doc(hidden)]
         #[track_caller]
@@ -346,9 +420,7 @@ fn expand_context(args: ContextArgs, func: ItemFn) -> syn::Result<TokenStream2>
 
         #(#attrs)*
         #vis #sig {
-            use eros::Context as _;
-            #(#clone_let_stmts)*
-            #awaited_call.with_context(|| #format_call)
+            #outer_body
         }
     })
 }
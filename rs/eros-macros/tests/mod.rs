@@ -12,7 +12,7 @@ fn test() {
     let error = test_function("test", "arg2".to_owned(), 42).unwrap_err();
     let inner_error = error.inner_ref();
     assert_eq!(inner_error.to_string(), "This is the error");
-    assert!(format!("{:?}", error).contains("\t- arg1 is test\n"));
+    assert!(format!("{:#?}", error).contains("\t- arg1 is test\n"));
 }
 
 // ── Async free functions ─────────────────────────────────────────────────────
@@ -26,7 +26,7 @@ async fn async_function(value: u32) -> eros::Result<u32> {
 async fn test_async_function_context_is_attached() {
     let error = async_function(99).await.unwrap_err();
     assert_eq!(error.inner_ref().to_string(), "async error");
-    assert!(format!("{:?}", error).contains("\t- async arg is 99\n"));
+    assert!(format!("{:#?}", error).contains("\t- async arg is 99\n"));
 }
 
 #[eros_macros::context("fetching {}", url)]
@@ -70,7 +70,7 @@ fn test_shared_ref_context_is_attached() {
     };
     let error = f.fetch("/api").unwrap_err();
     assert_eq!(error.inner_ref().to_string(), "connection refused");
-    assert!(format!("{:?}", error).contains("\t- fetching from http://localhost\n"));
+    assert!(format!("{:#?}", error).contains("\t- fetching from http://localhost\n"));
 }
 
 #[test]
@@ -79,7 +79,7 @@ fn test_shared_ref_multiple_format_args() {
         base_url: "http://localhost".to_owned(),
     };
     let error = f.fetch_multi_arg("/api/v1").unwrap_err();
-    let debug = format!("{:?}", error);
+    let debug = format!("{:#?}", error);
     assert!(debug.contains("http://localhost"));
     assert!(debug.contains("/api/v1"));
 }
@@ -120,7 +120,7 @@ fn test_mut_ref_context_is_attached() {
     let mut c = Counter { count: 95 };
     let error = c.increment(10).unwrap_err();
     assert_eq!(error.inner_ref().to_string(), "overflow");
-    assert!(format!("{:?}", error).contains("\t- increment failed at count 95\n"));
+    assert!(format!("{:#?}", error).contains("\t- increment failed at count 95\n"));
 }
 
 #[test]
@@ -158,7 +158,7 @@ fn test_value_receiver_context_is_attached() {
     let w = Wrapper("hello".to_owned());
     let error = w.consume().unwrap_err();
     assert_eq!(error.inner_ref().to_string(), "consumed and failed");
-    assert!(format!("{:?}", error).contains("\t- consuming wrapper\n"));
+    assert!(format!("{:#?}", error).contains("\t- consuming wrapper\n"));
 }
 
 #[test]
@@ -192,7 +192,7 @@ async fn test_async_shared_ref_context_is_attached() {
     };
     let error = client.fetch("/v1/items").await.unwrap_err();
     assert_eq!(error.inner_ref().to_string(), "timeout");
-    assert!(format!("{:?}", error).contains("\t- async fetch from api.example.com\n"));
+    assert!(format!("{:#?}", error).contains("\t- async fetch from api.example.com\n"));
 }
 
 #[tokio::test]
@@ -227,7 +227,7 @@ async fn test_async_mut_ref_context_is_attached() {
     };
     let error = q.push("c".into()).await.unwrap_err();
     assert_eq!(error.inner_ref().to_string(), "queue full");
-    assert!(format!("{:?}", error).contains("\t- push failed, queue len 2\n"));
+    assert!(format!("{:#?}", error).contains("\t- push failed, queue len 2\n"));
 }
 
 #[tokio::test]
@@ -247,7 +247,7 @@ fn no_args_function() -> eros::Result<()> {
 #[test]
 fn test_static_context_string_no_args() {
     let error = no_args_function().unwrap_err();
-    assert!(format!("{:?}", error).contains("\t- no format args at all\n"));
+    assert!(format!("{:#?}", error).contains("\t- no format args at all\n"));
 }
 
 #[eros_macros::context("a={} b={} c={}", a, b, c)]
@@ -258,7 +258,7 @@ fn three_arg_function(a: u8, b: u8, c: u8) -> eros::Result<()> {
 #[test]
 fn test_context_with_three_format_args() {
     let error = three_arg_function(1, 2, 3).unwrap_err();
-    assert!(format!("{:?}", error).contains("\t- a=1 b=2 c=3\n"));
+    assert!(format!("{:#?}", error).contains("\t- a=1 b=2 c=3\n"));
 }
 
 #[eros_macros::context("debug value is {:?}", value)]
@@ -269,7 +269,7 @@ fn debug_format_function(value: &[u8]) -> eros::Result<()> {
 #[test]
 fn test_context_with_debug_format_specifier() {
     let error = debug_format_function(&[1, 2, 3]).unwrap_err();
-    assert!(format!("{:?}", error).contains("[1, 2, 3]"));
+    assert!(format!("{:#?}", error).contains("[1, 2, 3]"));
 }
 
 // ── Auto format string (#[fmt("{}")] / #[fmt("{:?}")]) ───────────────────────────────
@@ -284,7 +284,7 @@ fn auto_display(#[fmt("{}")] name: &str, ignored: u32) -> eros::Result<()> {
 fn test_auto_display_single_param() {
     let error = auto_display("alice", 0).unwrap_err();
     assert_eq!(error.inner_ref().to_string(), "inner error");
-    assert!(format!("{:?}", error).contains("\t- name: alice\n"));
+    assert!(format!("{:#?}", error).contains("\t- name: alice\n"));
 }
 
 // Single #[fmt("{:?}")] param
@@ -299,7 +299,7 @@ fn auto_debug(#[fmt("{:?}")] flags: &Flags) -> eros::Result<()> {
 #[test]
 fn test_auto_debug_single_param() {
     let error = auto_debug(&Flags(0b1010)).unwrap_err();
-    let debug_out = format!("{:?}", error);
+    let debug_out = format!("{:#?}", error);
     // The context line should contain the Debug output of Flags
     assert!(debug_out.contains("flags: Flags(10)"));
 }
@@ -320,7 +320,7 @@ fn auto_mixed(
 #[test]
 fn test_auto_mixed_params_order_and_content() {
     let error = auto_mixed("bob", 99, &Mode("fast".into())).unwrap_err();
-    let debug_out = format!("{:?}", error);
+    let debug_out = format!("{:#?}", error);
     // Both annotated params appear
     assert!(debug_out.contains("user: bob"));
     assert!(debug_out.contains("mode: Mode(\"fast\")"));
@@ -361,7 +361,7 @@ fn test_auto_display_on_self_method() {
         name: "worker".into(),
     };
     let error = p.run("batch-42").unwrap_err();
-    let debug_out = format!("{:?}", error);
+    let debug_out = format!("{:#?}", error);
     assert!(debug_out.contains("job: batch-42"));
 }
 
@@ -374,7 +374,7 @@ async fn auto_async(#[fmt("{}")] endpoint: &str) -> eros::Result<()> {
 #[tokio::test]
 async fn test_auto_display_async() {
     let error = auto_async("https://api.example.com/v1").await.unwrap_err();
-    assert!(format!("{:?}", error).contains("endpoint: https://api.example.com/v1"));
+    assert!(format!("{:#?}", error).contains("endpoint: https://api.example.com/v1"));
 }
 
 // Auto format on async &mut self method
@@ -395,7 +395,7 @@ async fn test_auto_debug_async_mut_self() {
         stage: "encode".into(),
     };
     let error = p.execute(&[1, 2, 3]).await.unwrap_err();
-    assert!(format!("{:?}", error).contains("input: [1, 2, 3]"));
+    assert!(format!("{:#?}", error).contains("input: [1, 2, 3]"));
 }
 
 // ── Clone in format args ─────────────────────────────────────────────────────
@@ -411,7 +411,7 @@ fn owned_string_function(string: String) -> eros::Result<()> {
 fn test_owned_string_clone_context_is_attached() {
     let error = owned_string_function("hello".to_owned()).unwrap_err();
     assert_eq!(error.inner_ref().to_string(), "owned error");
-    assert!(format!("{:?}", error).contains("\t- processing hello\n"));
+    assert!(format!("{:#?}", error).contains("\t- processing hello\n"));
 }
 
 #[test]
@@ -432,7 +432,7 @@ fn two_owned_params(a: String, b: String) -> eros::Result<()> {
 #[test]
 fn test_two_owned_params_both_cloned() {
     let error = two_owned_params("foo".to_owned(), "bar".to_owned()).unwrap_err();
-    let debug = format!("{:?}", error);
+    let debug = format!("{:#?}", error);
     assert!(debug.contains("\t- a=foo b=bar\n"));
 }
 
@@ -445,7 +445,7 @@ fn mixed_owned_and_borrowed(name: String, id: u32) -> eros::Result<()> {
 #[test]
 fn test_mixed_clone_and_plain_ref() {
     let error = mixed_owned_and_borrowed("alice".to_owned(), 42).unwrap_err();
-    assert!(format!("{:?}", error).contains("\t- name=alice id=42\n"));
+    assert!(format!("{:#?}", error).contains("\t- name=alice id=42\n"));
 }
 
 // Same param cloned twice in the format string — only one `let` binding
@@ -458,7 +458,7 @@ fn duplicate_clone_same_param(val: String) -> eros::Result<()> {
 #[test]
 fn test_duplicate_clone_same_param_compiles_and_works() {
     let error = duplicate_clone_same_param("dup".to_owned()).unwrap_err();
-    assert!(format!("{:?}", error).contains("\t- first=dup again=dup\n"));
+    assert!(format!("{:#?}", error).contains("\t- first=dup again=dup\n"));
 }
 
 // Async free function with a cloned owned param.
@@ -471,7 +471,7 @@ async fn async_owned_function(payload: String) -> eros::Result<()> {
 async fn test_async_owned_clone_context_is_attached() {
     let error = async_owned_function("data".to_owned()).await.unwrap_err();
     assert_eq!(error.inner_ref().to_string(), "async owned error");
-    assert!(format!("{:?}", error).contains("\t- async processing data\n"));
+    assert!(format!("{:#?}", error).contains("\t- async processing data\n"));
 }
 
 #[tokio::test]
@@ -506,7 +506,7 @@ fn test_self_method_with_cloned_owned_param() {
         prefix: "queue-A".to_owned(),
     };
     let error = d.dispatch("job-1".to_owned()).unwrap_err();
-    let debug = format!("{:?}", error);
+    let debug = format!("{:#?}", error);
     assert!(debug.contains("dispatching job-1 via queue-A"));
 }
 
@@ -543,7 +543,7 @@ fn test_mut_self_method_clone_context_is_attached() {
         entries: vec!["a".into(), "b".into(), "c".into()],
     };
     let error = j.write("d".to_owned()).unwrap_err();
-    assert!(format!("{:?}", error).contains("\t- writing entry d\n"));
+    assert!(format!("{:#?}", error).contains("\t- writing entry d\n"));
 }
 
 #[test]
@@ -571,7 +571,7 @@ async fn test_async_self_method_clone_context_is_attached() {
         name: "w1".to_owned(),
     };
     let error = w.process("task-X".to_owned()).await.unwrap_err();
-    assert!(format!("{:?}", error).contains("\t- worker w1 processing task-X\n"));
+    assert!(format!("{:#?}", error).contains("\t- worker w1 processing task-X\n"));
 }
 
 // Custom Clone type — verifies the macro works for any Clone, not just String.
@@ -592,7 +592,7 @@ fn custom_clone_type(id: JobId) -> eros::Result<()> {
 #[test]
 fn test_custom_clone_type_display_in_context() {
     let error = custom_clone_type(JobId(7)).unwrap_err();
-    assert!(format!("{:?}", error).contains("\t- running job#7\n"));
+    assert!(format!("{:#?}", error).contains("\t- running job#7\n"));
 }
 
 // Debug format specifier with .clone().
@@ -604,5 +604,114 @@ fn debug_clone_function(data: Vec<u8>) -> eros::Result<()> {
 #[test]
 fn test_clone_with_debug_format_specifier() {
     let error = debug_clone_function(vec![10, 20, 30]).unwrap_err();
-    assert!(format!("{:?}", error).contains("payload=[10, 20, 30]"));
-}
\ No newline at end of file
+    assert!(format!("{:#?}", error).contains("payload=[10, 20, 30]"));
+}
+
+// ── Scoped context ──────────────────────────────────────────────────
+
+fn contexts(error: &eros::ErrorUnion) -> Vec<String> {
+    error.frames().map(|f| f.context().to_string()).collect()
+}
+
+async fn flaky(attempt: u32) -> eros::Result<u32> {
+    tokio::task::yield_now().await;
+    if attempt < 2 {
+        eros::bail!("attempt {} timed out", attempt);
+    }
+    Ok(attempt)
+}
+
+#[eros_macros::context(scope, "handling request {}", id)]
+async fn handle_with_retries(id: u64, failures: &mut Vec<eros::ErrorUnion>) -> eros::Result<u32> {
+    for attempt in 0.. {
+        match flaky(attempt).await {
+            Ok(attempt) => return Ok(attempt),
+            Err(error) => failures.push(error),
+        }
+    }
+    unreachable!()
+}
+
+#[eros_macros::context(scope, "handling request {}", id)]
+async fn handle_failing(id: u64) -> eros::Result<()> {
+    tokio::task::yield_now().await;
+    eros::bail!("request failed")
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_scope_is_attached_to_handled_errors_across_threads() {
+    let tasks: Vec<_> = (0..32)
+        .map(|id| {
+            tokio::spawn(async move {
+                let mut failures = Vec::new();
+                let attempt = handle_with_retries(id, &mut failures).await.unwrap();
+                (id, attempt, failures)
+            })
+        })
+        .collect();
+    for task in tasks {
+        let (id, attempt, failures) = task.await.unwrap();
+        assert_eq!(attempt, 2);
+        assert_eq!(failures.len(), 2);
+        for failure in &failures {
+            assert_eq!(contexts(failure), [format!("handling request {id}")]);
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_scope_is_attached_once_to_returned_error() {
+    let tasks: Vec<_> = (0..32).map(|id| tokio::spawn(handle_failing(id))).collect();
+    for (id, task) in tasks.into_iter().enumerate() {
+        let error = task.await.unwrap().unwrap_err();
+        assert_eq!(contexts(&error), [format!("handling request {id}")]);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_in_error_scope_nests_and_ends_with_the_future() {
+    use eros::ScopeFutureExt;
+
+    let error = async { flaky(0).await.unwrap_err() }
+        .in_error_scope("inner")
+        .in_error_scope("outer")
+        .await;
+    assert_eq!(contexts(&error), ["inner", "outer"]);
+    assert!(contexts(&flaky(0).await.unwrap_err()).is_empty());
+}
+
+#[eros_macros::context(scope, "loading {}", name)]
+fn load(name: &str) -> eros::Result<()> {
+    eros::bail!("missing")
+}
+
+#[test]
+fn test_sync_scope_is_attached() {
+    let error = load("config").unwrap_err();
+    assert_eq!(contexts(&error), ["loading config"]);
+}
+
+struct Counted(std::sync::atomic::AtomicUsize);
+
+impl std::fmt::Display for Counted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let formats = self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
+        write!(f, "formatted {formats} times")
+    }
+}
+
+#[eros_macros::context(scope, "{}", counted)]
+fn maybe_fail(counted: &Counted, fail: bool) -> eros::Result<()> {
+    if fail {
+        eros::bail!("failed");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_scope_is_only_formatted_for_errors() {
+    let counted = Counted(std::sync::atomic::AtomicUsize::new(0));
+    maybe_fail(&counted, false).unwrap();
+    let error = maybe_fail(&counted, true).unwrap_err();
+    assert_eq!(contexts(&error), ["formatted 1 times"]);
+}
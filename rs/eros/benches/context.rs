@@ -0,0 +1,56 @@
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use eros::{Context, ErrorUnion};
+
+#[derive(Debug)]
+struct NotFound;
+
+impl std::fmt::Display for NotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("not found")
+    }
+}
+
+impl std::error::Error for NotFound {}
+
+fn fail() -> Result<(), NotFound> {
+    Err(black_box(NotFound))
+}
+
+/// Creates an error with `frames` context frames, then handles and drops it, as in a retry loop
+fn create_and_drop(c: &mut Criterion, frames: usize) {
+    c.bench_function(&format!("create_and_drop/{frames}_frames"), |b| {
+        b.iter(|| {
+            let mut error: ErrorUnion<(NotFound,)> = ErrorUnion::new(NotFound);
+            for _ in 0..frames {
+                error = error.context(black_box("reading config"));
+            }
+            black_box(error)
+        })
+    });
+}
+
+fn context(c: &mut Criterion) {
+    for frames in [0, 1, 2, 3, 4, 8] {
+        create_and_drop(c, frames);
+    }
+    c.bench_function("result_context/2_frames", |b| {
+        b.iter(|| {
+            let result = fail()
+                .context(black_box("reading config"))
+                .context(black_box("starting server"));
+            black_box(result.is_err())
+        })
+    });
+    c.bench_function("into_single/2_frames", |b| {
+        b.iter(|| {
+            let error: ErrorUnion<(NotFound,)> = ErrorUnion::new(NotFound);
+            let error = error
+                .context(black_box("reading config"))
+                .context(black_box("starting server"));
+            black_box(error.into_single())
+        })
+    });
+}
+
+criterion_group!(benches, context);
+criterion_main!(benches);
@@ -0,0 +1,682 @@
+use alloc::vec::Vec;
+use core::cell::Cell;
+use core::fmt;
+use std::backtrace::Backtrace;
+use std::path::{Component, Path};
+use std::sync::OnceLock;
+
+use crate::ReportConfig;
+use crate::policy::{CapturePolicy, PolicyCell};
+
+/// Controls when a `Backtrace` is captured on creating an [`ErrorUnion`](crate::ErrorUnion).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BacktracePolicy {
+    /// Captures if enabled by the `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` env vars, same as
+    /// `Backtrace::capture`. Expected errors, see [`register_expected`](crate::register_expected),
+    /// are skipped.
+    #[default]
+    Env,
+    /// Always captures, regardless of the env vars and expected errors. e.g. in tests
+    Force,
+    /// Never captures
+    Disabled,
+    /// Captures for the given percentage of errors, regardless of the env vars. Expected errors,
+    /// see [`register_expected`](crate::register_expected), are skipped. Sampling is
+    /// deterministic rather than random: errors are counted across all threads and the first
+    /// `percent` of every 100 are captured.
+    Sample(u8),
+}
+
+impl CapturePolicy for BacktracePolicy {
+    fn to_parts(self) -> (u8, u8) {
+        match self {
+            BacktracePolicy::Env => (0, 0),
+            BacktracePolicy::Force => (1, 0),
+            BacktracePolicy::Disabled => (2, 0),
+            BacktracePolicy::Sample(percent) => (3, percent),
+        }
+    }
+
+    fn from_parts(kind: u8, percent: u8) -> Option<Self> {
+        match kind {
+            0 => Some(BacktracePolicy::Env),
+            1 => Some(BacktracePolicy::Force),
+            2 => Some(BacktracePolicy::Disabled),
+            3 => Some(BacktracePolicy::Sample(percent)),
+            _ => None,
+        }
+    }
+}
+
+std::thread_local! {
+    static POLICY_OVERRIDE: Cell<u16> = const { Cell::new(0) };
+}
+
+static POLICY: PolicyCell = PolicyCell::new(&POLICY_OVERRIDE);
+
+/// Sets the global backtrace policy
+pub fn set_backtrace_policy(policy: BacktracePolicy) {
+    POLICY.set(policy);
+}
+
+/// The backtrace policy currently in effect on this thread. This is the policy set by
+/// [`with_backtrace_policy`] if inside one, otherwise the global policy.
+pub fn backtrace_policy() -> BacktracePolicy {
+    POLICY.get()
+}
+
+/// Runs `f` with `policy` in effect on the current thread, overriding the global policy.
+/// e.g. forcing backtraces in a single test.
+pub fn with_backtrace_policy<R>(policy: BacktracePolicy, f: impl FnOnce() -> R) -> R {
+    POLICY.with(policy, f)
+}
+
+/// Captures a backtrace for a new error, according to the [`backtrace_policy`]. Errors are
+/// skipped if `expected` returns true, which is only called if the policy depends on it.
+pub(crate) fn capture(expected: impl FnOnce() -> bool) -> Backtrace {
+    match backtrace_policy() {
+        BacktracePolicy::Force => Backtrace::force_capture(),
+        BacktracePolicy::Disabled => Backtrace::disabled(),
+        _ if expected() => Backtrace::disabled(),
+        BacktracePolicy::Env => Backtrace::capture(),
+        BacktracePolicy::Sample(percent) => {
+            if POLICY.sample(percent) {
+                Backtrace::force_capture()
+            } else {
+                Backtrace::disabled()
+            }
+        }
+    }
+}
+
+/// How many frames of a backtrace are rendered in a report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BacktraceVerbosity {
+    /// Hides frames of the standard library, the runtime and `eros` itself
+    Short,
+    /// Every frame, with source snippets
+    Full,
+    /// Like `Short`, but also hides frames of dependencies, so only application code remains
+    App,
+}
+
+impl BacktraceVerbosity {
+    /// The verbosity set by the `EROS_BACKTRACE` env var, `short`, `full` or `app`, otherwise
+    /// `Short`. The env var is read once.
+    pub fn from_env() -> Self {
+        static FROM_ENV: OnceLock<BacktraceVerbosity> = OnceLock::new();
+        *FROM_ENV.get_or_init(|| {
+            std::env::var("EROS_BACKTRACE")
+                .ok()
+                .and_then(|value| BacktraceVerbosity::parse(&value))
+                .unwrap_or(BacktraceVerbosity::Short)
+        })
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if value.eq_ignore_ascii_case("short") {
+            Some(BacktraceVerbosity::Short)
+        } else if value.eq_ignore_ascii_case("full") {
+            Some(BacktraceVerbosity::Full)
+        } else if value.eq_ignore_ascii_case("app") {
+            Some(BacktraceVerbosity::App)
+        } else {
+            None
+        }
+    }
+}
+
+/// Configures how the backtrace of a report is rendered, with or without the `better_backtrace`
+/// feature. Set with [`ReportConfig::with_backtrace`](crate::ReportConfig::with_backtrace).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BacktraceConfig {
+    verbosity: Option<BacktraceVerbosity>,
+    hidden_modules: &'static [&'static str],
+    app_modules: &'static [&'static str],
+    color: Option<bool>,
+    source_snippets: Option<bool>,
+}
+
+impl BacktraceConfig {
+    pub const fn new() -> Self {
+        BacktraceConfig {
+            verbosity: None,
+            hidden_modules: &[],
+            app_modules: &[],
+            color: None,
+            source_snippets: None,
+        }
+    }
+
+    /// Defaults to [`BacktraceVerbosity::from_env`]
+    pub const fn with_verbosity(mut self, verbosity: BacktraceVerbosity) -> Self {
+        self.verbosity = Some(verbosity);
+        self
+    }
+
+    pub fn verbosity(&self) -> BacktraceVerbosity {
+        self.verbosity.unwrap_or_else(BacktraceVerbosity::from_env)
+    }
+
+    /// Frames of these modules are hidden at every verbosity, e.g. `&["tokio::"]`. Matched as a
+    /// prefix of the function name of the frame.
+    pub const fn with_hidden_modules(mut self, modules: &'static [&'static str]) -> Self {
+        self.hidden_modules = modules;
+        self
+    }
+
+    pub const fn hidden_modules(&self) -> &'static [&'static str] {
+        self.hidden_modules
+    }
+
+    /// Frames of these modules are always shown, e.g. in-house crates that would otherwise be
+    /// hidden as dependencies with [`BacktraceVerbosity::App`]. Matched as a prefix of the
+    /// function name of the frame.
+    pub const fn with_app_modules(mut self, modules: &'static [&'static str]) -> Self {
+        self.app_modules = modules;
+        self
+    }
+
+    pub const fn app_modules(&self) -> &'static [&'static str] {
+        self.app_modules
+    }
+
+    /// If the `better_backtrace` output is colored. Defaults to `true` when stderr is a terminal
+    /// and the `NO_COLOR` env var is not set.
+    pub const fn with_color(mut self, color: bool) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn color(&self) -> bool {
+        self.color.unwrap_or_else(crate::theme::auto_color)
+    }
+
+    /// If the source lines around each frame are included. Defaults to `true` only for
+    /// [`BacktraceVerbosity::Full`].
+    pub const fn with_source_snippets(mut self, source_snippets: bool) -> Self {
+        self.source_snippets = Some(source_snippets);
+        self
+    }
+
+    pub fn source_snippets(&self) -> bool {
+        self.source_snippets
+            .unwrap_or_else(|| self.verbosity() == BacktraceVerbosity::Full)
+    }
+
+    /// If a frame with the function name `symbol`, in `file`, is rendered
+    pub(crate) fn keeps_frame(&self, symbol: Option<&str>, file: Option<&str>) -> bool {
+        let name = symbol.map(module_path);
+        let matches =
+            |modules: &[&str]| name.is_some_and(|name| modules.iter().any(|m| name.starts_with(m)));
+        if matches(self.app_modules) {
+            return true;
+        }
+        if matches(self.hidden_modules) {
+            return false;
+        }
+        match self.verbosity() {
+            BacktraceVerbosity::Full => true,
+            _ if is_runtime_frame(symbol, file) => false,
+            BacktraceVerbosity::Short => true,
+            BacktraceVerbosity::App => !file.is_some_and(is_dependency_file),
+        }
+    }
+}
+
+impl Default for BacktraceConfig {
+    fn default() -> Self {
+        BacktraceConfig::new()
+    }
+}
+
+/// The path of the function of a frame, without the leading `<` or `&dyn ` of trait impls, e.g.
+/// `core::result::Result<T,E> as eros::IntoUnion<T,E>>::into_union`
+fn module_path(symbol: &str) -> &str {
+    let symbol = symbol.trim_start_matches(['<', '&']);
+    let symbol = symbol.strip_prefix("mut ").unwrap_or(symbol);
+    symbol.strip_prefix("dyn ").unwrap_or(symbol)
+}
+
+/// Frames of the standard library, the runtime, the unwinder and `eros` creating the error.
+/// Modules are matched by whole path segments and runtime symbols exactly, so user functions such
+/// as `main_loop` or `clone_repo` are kept.
+fn is_runtime_frame(symbol: Option<&str>, file: Option<&str>) -> bool {
+    const MODULES: &[&str] = &[
+        "std",
+        "core",
+        "alloc",
+        "test",
+        "backtrace",
+        "btparse",
+        "color_backtrace",
+        "eros::backtrace::capture",
+        "eros::error_union::ErrorUnion",
+        "eros::error_union::InnerBox",
+    ];
+    const SYMBOLS: &[&str] = &[
+        "main",
+        "<unknown>",
+        "_start",
+        "rust_begin_unwind",
+        "_rust_begin_unwind",
+        "start_thread",
+        "clone",
+        "__clone",
+        "clone3",
+        "__clone3",
+        "__scrt_common_main_seh",
+        "BaseThreadInitThunk",
+    ];
+    // Reserved for the implementation, so no user function starts with these
+    const RESERVED_PREFIXES: &[&str] = &["__rust_", "___rust_", "__pthread_", "__libc_start"];
+    if file.is_some_and(|file| file.starts_with("/rustc/")) {
+        return true;
+    }
+    symbol.is_some_and(|symbol| {
+        let path = module_path(symbol);
+        SYMBOLS.contains(&symbol)
+            || RESERVED_PREFIXES
+                .iter()
+                .any(|prefix| symbol.starts_with(prefix))
+            || MODULES.iter().any(|module| {
+                path.strip_prefix(module)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with(['<', ':']))
+            })
+    })
+}
+
+/// Files of crates downloaded by cargo, from a registry or git
+fn is_dependency_file(file: &str) -> bool {
+    Path::new(file)
+        .components()
+        .any(|component| component == Component::Normal(".cargo".as_ref()))
+}
+
+/// A frame of the `Display` output of a [`Backtrace`]
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Frame<'a> {
+    pub(crate) index: usize,
+    pub(crate) symbol: &'a str,
+    /// e.g. `./src/main.rs:10:5`
+    pub(crate) location: Option<&'a str>,
+}
+
+impl Frame<'_> {
+    /// The file, line and column of the location
+    fn source_location(&self) -> Option<(&str, u32, u32)> {
+        let mut parts = self.location?.rsplitn(3, ':');
+        let column = parts.next()?.parse().ok()?;
+        let line = parts.next()?.parse().ok()?;
+        Some((parts.next()?, line, column))
+    }
+}
+
+/// Parses the frames of `Backtrace`'s `Display` output, e.g.
+/// ```text
+///    0: app::main
+///              at ./src/main.rs:10:5
+/// ```
+pub(crate) fn parse_frames(text: &str) -> Vec<Frame<'_>> {
+    let mut frames: Vec<Frame<'_>> = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if let Some(location) = line.strip_prefix("at ") {
+            if let Some(frame) = frames.last_mut() {
+                frame.location.get_or_insert(location);
+            }
+        } else if let Some((index, symbol)) = line.split_once(": ")
+            && let Ok(index) = index.parse()
+        {
+            frames.push(Frame {
+                index,
+                symbol,
+                location: None,
+            });
+        }
+    }
+    frames
+}
+
+/// Writes `backtrace` in the same layout as its `Display` output, with the frames filtered
+/// according to the [`BacktraceConfig`] of `report_config` and styled with its theme
+pub(crate) fn write_filtered(
+    backtrace: &Backtrace,
+    report_config: &ReportConfig,
+    formatter: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    let config = report_config.backtrace();
+    let color = report_config.color();
+    let text = alloc::format!("{backtrace}");
+    let frames = parse_frames(&text);
+    if frames.is_empty() {
+        return fmt::Display::fmt(backtrace, formatter);
+    }
+    let source_snippets = config.source_snippets();
+    let mut hidden = 0;
+    for frame in &frames {
+        let file = frame.source_location().map(|(file, ..)| file);
+        if !config.keeps_frame(Some(frame.symbol), file) {
+            hidden += 1;
+            continue;
+        }
+        write_hidden(hidden, formatter)?;
+        hidden = 0;
+        write!(formatter, "{:>4}: ", frame.index)?;
+        report_config
+            .theme()
+            .backtrace()
+            .paint(color, formatter, |f| f.write_str(frame.symbol))?;
+        writeln!(formatter)?;
+        match (frame.source_location(), frame.location) {
+            (Some((file, line, column)), _) => {
+                formatter.write_str("             at ")?;
+                crate::report::write_source_location(
+                    file,
+                    line,
+                    Some(column),
+                    report_config,
+                    formatter,
+                )?;
+                writeln!(formatter)?;
+            }
+            (None, Some(location)) => writeln!(formatter, "             at {location}")?,
+            (None, None) => {}
+        }
+        if source_snippets && let Some((file, line, column)) = frame.source_location() {
+            crate::report::write_source_snippet(file, line, column, formatter)?;
+        }
+    }
+    write_hidden(hidden, formatter)
+}
+
+fn write_hidden(hidden: usize, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match hidden {
+        0 => Ok(()),
+        1 => writeln!(formatter, "      ⋮ 1 frame hidden ⋮"),
+        _ => writeln!(formatter, "      ⋮ {hidden} frames hidden ⋮"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::backtrace::BacktraceStatus;
+    use std::fmt;
+
+    use super::*;
+    use crate::{ErrorUnion, SendSyncError};
+
+    #[derive(Debug)]
+    struct NotFound;
+
+    impl fmt::Display for NotFound {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("not found")
+        }
+    }
+
+    impl std::error::Error for NotFound {}
+
+    #[derive(Debug)]
+    struct Unexpected;
+
+    impl fmt::Display for Unexpected {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("unexpected")
+        }
+    }
+
+    impl std::error::Error for Unexpected {}
+
+    fn status<T: SendSyncError>(error: T) -> BacktraceStatus {
+        let union: ErrorUnion<(T,)> = ErrorUnion::new(error);
+        union.backtrace().status()
+    }
+
+    fn expected_status<T: SendSyncError>(error: T) -> BacktraceStatus {
+        let union: ErrorUnion<(T,)> = ErrorUnion::expected(error);
+        union.backtrace().status()
+    }
+
+    #[test]
+    fn override_is_restored() {
+        let outer = backtrace_policy();
+        with_backtrace_policy(BacktracePolicy::Disabled, || {
+            assert_eq!(backtrace_policy(), BacktracePolicy::Disabled);
+            with_backtrace_policy(BacktracePolicy::Sample(30), || {
+                assert_eq!(backtrace_policy(), BacktracePolicy::Sample(30))
+            });
+            assert_eq!(backtrace_policy(), BacktracePolicy::Disabled);
+        });
+        assert_eq!(backtrace_policy(), outer);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // capturing a backtrace is not supported by Miri
+    fn force_and_disabled() {
+        with_backtrace_policy(BacktracePolicy::Force, || {
+            assert_eq!(status(Unexpected), BacktraceStatus::Captured);
+        });
+        with_backtrace_policy(BacktracePolicy::Disabled, || {
+            assert_eq!(status(Unexpected), BacktraceStatus::Disabled);
+        });
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // capturing a backtrace is not supported by Miri
+    fn sample_captures_a_percentage() {
+        with_backtrace_policy(BacktracePolicy::Sample(100), || {
+            assert_eq!(status(Unexpected), BacktraceStatus::Captured);
+        });
+        with_backtrace_policy(BacktracePolicy::Sample(0), || {
+            assert_eq!(status(Unexpected), BacktraceStatus::Disabled);
+        });
+    }
+
+    #[test]
+    fn sampling_counts_every_error() {
+        // The global cell is shared with the other tests, which create errors concurrently
+        std::thread_local! {
+            static LOCAL: Cell<u16> = const { Cell::new(0) };
+        }
+        let cell = PolicyCell::new(&LOCAL);
+        assert_eq!((0..100).filter(|_| cell.sample(25)).count(), 25);
+        assert_eq!((0..50).filter(|_| cell.sample(25)).count(), 25);
+        assert_eq!((0..100).filter(|_| cell.sample(0)).count(), 0);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // capturing a backtrace is not supported by Miri
+    fn expected_errors_are_skipped_unless_forced() {
+        with_backtrace_policy(BacktracePolicy::Sample(100), || {
+            assert_eq!(expected_status(Unexpected), BacktraceStatus::Disabled);
+            assert_eq!(status(Unexpected), BacktraceStatus::Captured);
+        });
+        with_backtrace_policy(BacktracePolicy::Force, || {
+            assert_eq!(expected_status(Unexpected), BacktraceStatus::Captured);
+        });
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // capturing a backtrace is not supported by Miri
+    fn registered_expected_errors_are_skipped_however_created() {
+        crate::register_expected::<NotFound>();
+        fn propagate() -> Result<(), ErrorUnion> {
+            Err(NotFound)?;
+            Ok(())
+        }
+        with_backtrace_policy(BacktracePolicy::Sample(100), || {
+            assert_eq!(status(NotFound), BacktraceStatus::Disabled);
+            let error = propagate().unwrap_err();
+            assert_eq!(error.backtrace().status(), BacktraceStatus::Disabled);
+            assert_eq!(status(Unexpected), BacktraceStatus::Captured);
+        });
+        with_backtrace_policy(BacktracePolicy::Force, || {
+            assert_eq!(status(NotFound), BacktraceStatus::Captured);
+        });
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // capturing a backtrace is not supported by Miri
+    fn force_backtrace_captures_later() {
+        let union: ErrorUnion<(Unexpected,)> =
+            with_backtrace_policy(BacktracePolicy::Disabled, || ErrorUnion::new(Unexpected));
+        assert_eq!(union.backtrace().status(), BacktraceStatus::Disabled);
+        let union = union.force_backtrace();
+        assert_eq!(union.backtrace().status(), BacktraceStatus::Captured);
+    }
+
+    #[test]
+    fn verbosity_is_parsed_from_env_value() {
+        assert_eq!(
+            BacktraceVerbosity::parse("short"),
+            Some(BacktraceVerbosity::Short)
+        );
+        assert_eq!(
+            BacktraceVerbosity::parse(" Full "),
+            Some(BacktraceVerbosity::Full)
+        );
+        assert_eq!(
+            BacktraceVerbosity::parse("APP"),
+            Some(BacktraceVerbosity::App)
+        );
+        assert_eq!(BacktraceVerbosity::parse("1"), None);
+    }
+
+    #[test]
+    fn frames_are_parsed_from_display_output() {
+        let text =
+            "   0: app::load\n             at ./src/main.rs:10:5\n   1: main\n   2: <unknown>\n";
+        let frames = parse_frames(text);
+        assert_eq!(
+            frames,
+            [
+                Frame {
+                    index: 0,
+                    symbol: "app::load",
+                    location: Some("./src/main.rs:10:5"),
+                },
+                Frame {
+                    index: 1,
+                    symbol: "main",
+                    location: None,
+                },
+                Frame {
+                    index: 2,
+                    symbol: "<unknown>",
+                    location: None,
+                },
+            ]
+        );
+        assert_eq!(frames[0].source_location(), Some(("./src/main.rs", 10, 5)));
+    }
+
+    const RUNTIME: (&str, &str) = (
+        "<alloc::boxed::Box<F> as core::ops::function::FnOnce<A>>::call_once",
+        "/rustc/hash/library/alloc/src/boxed.rs",
+    );
+    const TOKIO: (&str, &str) = (
+        "tokio::runtime::park::CachedParkThread::block_on",
+        "/home/user/.cargo/registry/src/index/tokio-1.0.0/src/runtime/park.rs",
+    );
+    const IN_HOUSE: (&str, &str) = (
+        "acme_db::query",
+        "/home/user/.cargo/registry/src/index/acme_db-1.0.0/src/lib.rs",
+    );
+    const APP: (&str, &str) = ("app::load", "./src/main.rs");
+
+    fn kept(config: &BacktraceConfig) -> Vec<&'static str> {
+        [RUNTIME, TOKIO, IN_HOUSE, APP]
+            .into_iter()
+            .filter(|(symbol, file)| config.keeps_frame(Some(symbol), Some(file)))
+            .map(|(symbol, _)| symbol)
+            .collect()
+    }
+
+    #[test]
+    fn frames_are_filtered_by_verbosity_and_modules() {
+        let full = BacktraceConfig::new().with_verbosity(BacktraceVerbosity::Full);
+        assert_eq!(kept(&full), [RUNTIME.0, TOKIO.0, IN_HOUSE.0, APP.0]);
+
+        let short = BacktraceConfig::new().with_verbosity(BacktraceVerbosity::Short);
+        assert_eq!(kept(&short), [TOKIO.0, IN_HOUSE.0, APP.0]);
+        let short = short.with_hidden_modules(&["tokio::"]);
+        assert_eq!(kept(&short), [IN_HOUSE.0, APP.0]);
+
+        let app = BacktraceConfig::new().with_verbosity(BacktraceVerbosity::App);
+        assert_eq!(kept(&app), [APP.0]);
+        let app = app.with_app_modules(&["acme_"]);
+        assert_eq!(kept(&app), [IN_HOUSE.0, APP.0]);
+    }
+
+    #[test]
+    fn runtime_frames_match_whole_segments() {
+        assert!(is_runtime_frame(Some("main"), None));
+        assert!(is_runtime_frame(Some("clone"), None));
+        assert!(is_runtime_frame(Some("std::rt::lang_start"), None));
+        assert!(is_runtime_frame(Some("__rust_begin_short_backtrace"), None));
+        assert!(is_runtime_frame(
+            Some("eros::error_union::ErrorUnion<T>::new"),
+            None
+        ));
+        assert!(!is_runtime_frame(Some("main_loop::run"), None));
+        assert!(!is_runtime_frame(Some("clone_repo"), None));
+        assert!(!is_runtime_frame(Some("app::main"), None));
+        assert!(!is_runtime_frame(Some("stdout_logger::write"), None));
+        assert!(!is_runtime_frame(Some("tests::test_utils"), None));
+    }
+
+    #[test]
+    fn color_and_source_snippets_can_be_set() {
+        let config = BacktraceConfig::new()
+            .with_verbosity(BacktraceVerbosity::Full)
+            .with_color(false);
+        assert!(!config.color());
+        assert!(config.source_snippets());
+        let config = config.with_source_snippets(false);
+        assert!(!config.source_snippets());
+    }
+
+    struct Filtered<'a>(&'a Backtrace, BacktraceConfig);
+
+    impl fmt::Display for Filtered<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let config = ReportConfig::new()
+                .with_color(false)
+                .with_backtrace(self.1.clone());
+            write_filtered(self.0, &config, f)
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // capturing a backtrace is not supported by Miri
+    fn plain_backtrace_is_filtered() {
+        let backtrace = Backtrace::force_capture();
+        let config = BacktraceConfig::new().with_verbosity(BacktraceVerbosity::Short);
+        let short = Filtered(&backtrace, config.clone()).to_string();
+        assert!(short.contains("plain_backtrace_is_filtered"), "{short}");
+        assert!(short.contains("frames hidden"), "{short}");
+        assert!(!short.contains(": std::"), "{short}");
+
+        let full = config
+            .with_verbosity(BacktraceVerbosity::Full)
+            .with_source_snippets(false);
+        let full = Filtered(&backtrace, full).to_string();
+        assert!(full.contains(": std::"), "{full}");
+        assert!(!full.contains("frames hidden"), "{full}");
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // capturing a backtrace is not supported by Miri
+    fn plain_backtrace_has_source_snippets() {
+        let line = line!() + 1;
+        let backtrace = Backtrace::force_capture();
+        let config = BacktraceConfig::new()
+            .with_verbosity(BacktraceVerbosity::Short)
+            .with_source_snippets(true);
+        let rendered = Filtered(&backtrace, config).to_string();
+        let snippet =
+            alloc::format!("{line} |         let backtrace = Backtrace::force_capture();");
+        assert!(rendered.contains(&snippet), "{rendered}");
+    }
+}
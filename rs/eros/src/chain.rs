@@ -0,0 +1,200 @@
+use core::error::Error;
+use core::iter::FusedIterator;
+
+#[cfg(feature = "context")]
+use crate::context::{ContextFrame, ContextSource};
+use crate::{ErrorUnion, type_set::TypeSet};
+
+/// Iterator over every error in an [`ErrorUnion`], in causal order. Created with [`ErrorUnion::chain`].
+///
+/// Yields the inner error, then its `source()` chain, then each error added as context, in the
+/// order it was added, each followed by its own `source()` chain. Sensitive context is skipped,
+/// unless the [`redaction_policy`](crate::redaction_policy) reveals it.
+#[derive(Clone)]
+pub struct Chain<'a> {
+    next: Option<&'a (dyn Error + 'static)>,
+    #[cfg(feature = "context")]
+    frames: core::slice::Iter<'a, ContextFrame>,
+}
+
+impl<'a> Iterator for Chain<'a> {
+    type Item = &'a (dyn Error + 'static);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        #[cfg(feature = "context")]
+        while self.next.is_none() {
+            self.next = context_error(&self.frames.next()?.context);
+        }
+        let error = self.next.take()?;
+        self.next = error.source();
+        Some(error)
+    }
+}
+
+/// The error of context added as an error, `None` for sensitive context when redacting
+#[cfg(feature = "context")]
+fn context_error(context: &ContextSource) -> Option<&(dyn Error + 'static)> {
+    match context {
+        ContextSource::Error(error) => Some(&**error as &(dyn Error + 'static)),
+        ContextSource::Sensitive(context) if !crate::redact::should_redact() => {
+            context_error(context)
+        }
+        _ => None,
+    }
+}
+
+impl FusedIterator for Chain<'_> {}
+
+impl<E> ErrorUnion<E>
+where
+    E: TypeSet,
+{
+    /// Iterates over every error in causal order. The inner error, its `source()` chain, then each
+    /// error added as context with its own `source()` chain. See [`Chain`].
+    pub fn chain(&self) -> Chain<'_> {
+        Chain {
+            next: Some(&self.inner.error as &(dyn Error + 'static)),
+            #[cfg(feature = "context")]
+            frames: self.inner.context.iter(),
+        }
+    }
+
+    /// The lowest level cause of the inner error, the last error in its `source()` chain.
+    /// This is the inner error itself if it has no source.
+    pub fn root_cause(&self) -> &(dyn Error + 'static) {
+        let mut error: &(dyn Error + 'static) = &self.inner.error;
+        while let Some(source) = error.source() {
+            error = source;
+        }
+        error
+    }
+
+    /// Finds the first error of type `T` in [`ErrorUnion::chain`]
+    pub fn find<T: Error + 'static>(&self) -> Option<&T> {
+        self.chain().find_map(|error| error.downcast_ref::<T>())
+    }
+
+    /// Finds every error of type `T` in [`ErrorUnion::chain`]
+    pub fn find_all<T: Error + 'static>(&self) -> impl Iterator<Item = &T> {
+        self.chain().filter_map(|error| error.downcast_ref::<T>())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use core::fmt;
+    use std::io;
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct Wrapper {
+        name: &'static str,
+        source: Option<Box<dyn Error + Send + Sync>>,
+    }
+
+    impl Wrapper {
+        fn new(name: &'static str) -> Self {
+            Wrapper { name, source: None }
+        }
+
+        fn with_source(name: &'static str, source: impl Error + Send + Sync + 'static) -> Self {
+            Wrapper {
+                name,
+                source: Some(Box::new(source)),
+            }
+        }
+    }
+
+    impl fmt::Display for Wrapper {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.name)
+        }
+    }
+
+    impl Error for Wrapper {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            self.source.as_deref().map(|e| e as &(dyn Error + 'static))
+        }
+    }
+
+    fn not_found() -> io::Error {
+        io::Error::new(io::ErrorKind::NotFound, "missing")
+    }
+
+    #[test]
+    fn chain_without_context_walks_sources() {
+        let union: ErrorUnion<(Wrapper,)> = ErrorUnion::new(Wrapper::with_source(
+            "request",
+            Wrapper::with_source("connection", not_found()),
+        ));
+
+        let messages = union.chain().map(|e| e.to_string()).collect::<Vec<_>>();
+        assert_eq!(messages, vec!["request", "connection", "missing"]);
+        assert_eq!(union.root_cause().to_string(), "missing");
+    }
+
+    #[test]
+    fn root_cause_of_error_without_source_is_inner() {
+        let union: ErrorUnion<(Wrapper,)> = ErrorUnion::new(Wrapper::new("leaf"));
+        assert_eq!(union.root_cause().to_string(), "leaf");
+        assert_eq!(union.chain().count(), 1);
+    }
+
+    #[test]
+    fn find_searches_the_whole_chain() {
+        let union: ErrorUnion<(Wrapper,)> =
+            ErrorUnion::new(Wrapper::with_source("request", not_found()));
+
+        let io_error = union.find::<io::Error>().unwrap();
+        assert_eq!(io_error.kind(), io::ErrorKind::NotFound);
+        assert!(union.find::<fmt::Error>().is_none());
+        assert_eq!(union.find::<Wrapper>().unwrap().name, "request");
+    }
+
+    #[cfg(feature = "context")]
+    #[test]
+    fn chain_includes_context_errors_in_order() {
+        let union: ErrorUnion<(Wrapper,)> =
+            ErrorUnion::new(Wrapper::with_source("request", Wrapper::new("connection")));
+        let union = union
+            .context("plain context")
+            .context(Box::new(Wrapper::with_source("retry", not_found()))
+                as Box<dyn crate::SendSyncError>)
+            .context(Box::new(io::Error::other("shutdown")) as Box<dyn crate::SendSyncError>)
+            .sensitive_context(Box::new(Wrapper::new("secret")) as Box<dyn crate::SendSyncError>);
+
+        let messages = |policy| {
+            crate::with_redaction_policy(policy, || {
+                union.chain().map(|e| e.to_string()).collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(
+            messages(crate::RedactionPolicy::Redact),
+            vec!["request", "connection", "retry", "missing", "shutdown"]
+        );
+        assert_eq!(
+            messages(crate::RedactionPolicy::Reveal),
+            vec![
+                "request",
+                "connection",
+                "retry",
+                "missing",
+                "shutdown",
+                "secret"
+            ]
+        );
+        assert_eq!(union.root_cause().to_string(), "connection");
+
+        let kinds = union
+            .find_all::<io::Error>()
+            .map(|e| e.kind())
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, vec![io::ErrorKind::NotFound, io::ErrorKind::Other]);
+        assert!(
+            union
+                .find_all::<io::Error>()
+                .any(|e| e.kind() == io::ErrorKind::NotFound)
+        );
+    }
+}
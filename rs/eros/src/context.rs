@@ -1,48 +1,264 @@
-use alloc::{borrow::Cow, boxed::Box, string::String};
+use alloc::{borrow::Cow, boxed::Box, string::String, vec::Vec};
+use core::any::Any;
 use core::fmt::Display;
 use core::result::Result;
 
 use crate::{ErrorUnion, SendSyncError, type_set::TypeSet};
 
+/// What a [`ContextFrame`] holds. Every kind but [`FrameKind::Context`] is an annotation, which is
+/// rendered in its own section, similar to rustc diagnostics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameKind {
+    /// What was being done when the error occurred
+    Context,
+    /// Additional information, e.g. "note: the config is read from $HOME"
+    Note,
+    /// A hint on how to fix the error, e.g. "help: run `foo init` first"
+    Help,
+    /// Something that may be related to the error, e.g. "warning: the cache is stale"
+    Warning,
+}
+
+impl FrameKind {
+    /// The kinds that are annotations, in the order their sections are rendered
+    pub const ANNOTATIONS: [FrameKind; 3] = [FrameKind::Warning, FrameKind::Note, FrameKind::Help];
+
+    pub fn is_annotation(self) -> bool {
+        self != FrameKind::Context
+    }
+
+    /// The lowercase name, e.g. `help`
+    pub fn label(self) -> &'static str {
+        match self {
+            FrameKind::Context => "context",
+            FrameKind::Note => "note",
+            FrameKind::Help => "help",
+            FrameKind::Warning => "warning",
+        }
+    }
+}
+
+impl core::fmt::Display for FrameKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// How many context frames are stored inline, in the same allocation as the error, before
+/// spilling to a separate heap allocation. Most errors carry one to three frames.
+#[cfg(feature = "context")]
+pub(crate) const INLINE_FRAMES: usize = 3;
+
+/// The context frames of an [`ErrorUnion`]
+#[cfg(feature = "context")]
+pub(crate) type Frames = smallvec::SmallVec<[ContextFrame; INLINE_FRAMES]>;
+
+/// Adds `frame` as the outermost context of `frames`, but inside the scopes the error was
+/// created in that are still active, since it was added within them
+#[cfg(feature = "context")]
+pub(crate) fn push_frame(frames: &mut Frames, frame: ContextFrame) {
+    #[cfg(feature = "scope")]
+    {
+        let index = crate::scope::insertion_index(frames);
+        frames.insert(index, frame);
+    }
+    #[cfg(not(feature = "scope"))]
+    frames.push(frame);
+}
+
+/// The frame added last with [`push_frame`]. This is not always the last frame, since the frames
+/// of scopes that are still active stay outside it.
+#[cfg(feature = "context")]
+pub(crate) fn last_pushed(frames: &mut Frames) -> Option<&mut ContextFrame> {
+    #[cfg(feature = "scope")]
+    return frames.iter_mut().rfind(|frame| frame.scope.is_none());
+    #[cfg(not(feature = "scope"))]
+    return frames.last_mut();
+}
+
+/// A single piece of context attached to an [`ErrorUnion`], in the order it was added.
+/// Yielded by [`ErrorUnion::frames`].
 #[derive(Debug)]
-pub struct ErosContext {
+pub struct ContextFrame {
     pub(crate) context: ContextSource,
+    pub(crate) kind: FrameKind,
     #[cfg(feature = "location")]
     pub(crate) location: &'static core::panic::Location<'static>,
     #[cfg(feature = "user_context")]
     pub(crate) is_user_facing: bool,
+    /// Boxed, since most frames have no attachments, so a frame only pays for a pointer
+    #[allow(clippy::box_collection)]
+    pub(crate) attachments: Option<Box<Vec<Box<dyn Any + Send + Sync>>>>,
+    /// The scope this frame was captured from, while context added to the error may still be
+    /// inside it
+    #[cfg(feature = "scope")]
+    pub(crate) scope: Option<usize>,
 }
 
-impl ErosContext {
+impl ContextFrame {
+    #[cfg(feature = "context")]
+    #[cfg_attr(feature = "location", track_caller)]
+    pub(crate) fn new(context: ContextSource) -> Self {
+        Self {
+            context,
+            kind: FrameKind::Context,
+            #[cfg(feature = "location")]
+            location: core::panic::Location::caller(),
+            #[cfg(feature = "user_context")]
+            is_user_facing: false,
+            attachments: None,
+            #[cfg(feature = "scope")]
+            scope: None,
+        }
+    }
+
+    #[cfg(feature = "context")]
     #[cfg_attr(feature = "location", track_caller)]
-    pub fn new(context: ContextSource) -> Self {
+    pub(crate) fn new_annotation(kind: FrameKind, context: ContextSource) -> Self {
         Self {
             context,
+            kind,
             #[cfg(feature = "location")]
             location: core::panic::Location::caller(),
             #[cfg(feature = "user_context")]
             is_user_facing: false,
+            attachments: None,
+            #[cfg(feature = "scope")]
+            scope: None,
         }
     }
 
     #[cfg(feature = "user_context")]
     #[cfg_attr(feature = "location", track_caller)]
-    pub fn new_user_facing(context: ContextSource) -> Self {
+    pub(crate) fn new_user_facing(context: ContextSource) -> Self {
         Self {
             context,
+            kind: FrameKind::Context,
             #[cfg(feature = "location")]
             location: core::panic::Location::caller(),
             is_user_facing: true,
+            attachments: None,
+            #[cfg(feature = "scope")]
+            scope: None,
         }
     }
+
+    /// The message or error this frame holds
+    pub fn context(&self) -> &ContextSource {
+        &self.context
+    }
+
+    /// Mutable access to the message or error this frame holds. Useful for rewriting context
+    /// before an error crosses a trust boundary.
+    pub fn context_mut(&mut self) -> &mut ContextSource {
+        &mut self.context
+    }
+
+    /// If this frame is context or an annotation
+    pub fn kind(&self) -> FrameKind {
+        self.kind
+    }
+
+    /// Where this context was added
+    #[cfg(feature = "location")]
+    pub fn location(&self) -> &'static core::panic::Location<'static> {
+        self.location
+    }
+
+    /// If this context was added as user facing, e.g. with `user_context`
+    #[cfg(feature = "user_context")]
+    pub fn is_user_facing(&self) -> bool {
+        self.is_user_facing
+    }
+
+    /// Marks or unmarks this context as user facing
+    #[cfg(feature = "user_context")]
+    pub fn set_user_facing(&mut self, is_user_facing: bool) {
+        self.is_user_facing = is_user_facing;
+    }
+
+    /// Attaches a typed value to this frame, e.g. a status code or a request id, for custom
+    /// renderers and handlers. Attachments are not rendered, but [`KeyValue`]s are emitted when
+    /// the error is logged.
+    pub fn attach<T: Any + Send + Sync>(&mut self, value: T) {
+        self.attachments
+            .get_or_insert_with(Default::default)
+            .push(Box::new(value));
+    }
+
+    /// The values of type `T` attached to this frame, in the order they were attached
+    pub fn attachments<T: Any>(&self) -> impl Iterator<Item = &T> {
+        self.attachments
+            .iter()
+            .flat_map(|attachments| attachments.iter())
+            .filter_map(|attachment| (**attachment).downcast_ref::<T>())
+    }
+}
+
+/// A named value attached to a [`ContextFrame`] with [`ContextFrame::attach`]. Unlike other
+/// attachments, key-values are emitted as the `error.attachments` field when the error is logged,
+/// see `LogExt`. The value is formatted when logged, so a [`Secret`](crate::Secret) value follows
+/// the redaction policy in effect then.
+pub struct KeyValue {
+    key: Cow<'static, str>,
+    value: Box<dyn Display + Send + Sync>,
+}
+
+impl KeyValue {
+    pub fn new(
+        key: impl Into<Cow<'static, str>>,
+        value: impl Display + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            key: key.into(),
+            value: Box::new(value),
+        }
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn value(&self) -> &(dyn Display + Send + Sync) {
+        &*self.value
+    }
+}
+
+impl core::fmt::Debug for KeyValue {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("KeyValue")
+            .field("key", &self.key)
+            .field("value", &format_args!("{}", self.value))
+            .finish()
+    }
 }
 
 /// The underlying data
-#[derive(Debug)]
 pub enum ContextSource {
     Static(&'static str),
     Owned(String),
     Error(Box<dyn SendSyncError>),
+    /// A value, e.g. an id or a path, that is only formatted when rendered. Created with
+    /// [`ContextSource::display`] or `.context_display(value)`.
+    Display(Box<dyn Display + Send + Sync>),
+    /// Context that is rendered as [`REDACTED`](crate::REDACTED) when the
+    /// [`redaction_policy`](crate::redaction_policy) is to redact. Created with [`Secret`](crate::Secret).
+    Sensitive(Box<ContextSource>),
+    /// A localizable user facing message
+    #[cfg(feature = "user_context")]
+    Message(crate::UserMessage),
+}
+
+impl ContextSource {
+    /// Context from a value that is only formatted when rendered, not when added
+    pub fn display<D: Display + Send + Sync + 'static>(value: D) -> Self {
+        ContextSource::Display(Box::new(value))
+    }
+
+    /// If this context is sensitive
+    pub fn is_sensitive(&self) -> bool {
+        matches!(self, ContextSource::Sensitive(_))
+    }
 }
 
 impl core::fmt::Display for ContextSource {
@@ -51,6 +267,34 @@ impl core::fmt::Display for ContextSource {
             ContextSource::Static(s) => write!(f, "{}", s),
             ContextSource::Owned(s) => write!(f, "{}", s),
             ContextSource::Error(e) => write!(f, "{}", e),
+            ContextSource::Display(d) => write!(f, "{}", d),
+            #[cfg(feature = "user_context")]
+            ContextSource::Message(m) => write!(f, "{}", m),
+            ContextSource::Sensitive(_) if crate::redact::should_redact() => {
+                f.write_str(crate::REDACTED)
+            }
+            ContextSource::Sensitive(c) => write!(f, "{}", c),
+        }
+    }
+}
+
+impl core::fmt::Debug for ContextSource {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ContextSource::Static(s) => f.debug_tuple("Static").field(s).finish(),
+            ContextSource::Owned(s) => f.debug_tuple("Owned").field(s).finish(),
+            ContextSource::Error(e) => f.debug_tuple("Error").field(e).finish(),
+            ContextSource::Display(d) => f
+                .debug_tuple("Display")
+                .field(&format_args!("{}", d))
+                .finish(),
+            #[cfg(feature = "user_context")]
+            ContextSource::Message(m) => f.debug_tuple("Message").field(m).finish(),
+            ContextSource::Sensitive(_) if crate::redact::should_redact() => f
+                .debug_tuple("Sensitive")
+                .field(&format_args!("{}", crate::REDACTED))
+                .finish(),
+            ContextSource::Sensitive(c) => f.debug_tuple("Sensitive").field(c).finish(),
         }
     }
 }
@@ -76,6 +320,19 @@ impl From<Cow<'static, str>> for ContextSource {
     }
 }
 
+impl From<core::fmt::Arguments<'_>> for ContextSource {
+    /// Does not allocate if the arguments are a literal without placeholders, e.g. `format_args!("reading config")`.
+    /// Otherwise they are formatted right away, since `Arguments` borrows the values it formats.
+    /// For context that is only formatted when rendered, use `.context_display(value)`, or
+    /// `.with_context(..)` to only build it on error.
+    fn from(args: core::fmt::Arguments<'_>) -> ContextSource {
+        match args.as_str() {
+            Some(s) => ContextSource::Static(s),
+            None => ContextSource::Owned(alloc::fmt::format(args)),
+        }
+    }
+}
+
 impl From<Box<dyn SendSyncError>> for ContextSource {
     fn from(e: Box<dyn SendSyncError>) -> Self {
         ContextSource::Error(e)
@@ -101,6 +358,85 @@ pub trait Context {
     where
         F: FnOnce() -> C;
 
+    /// Adds additional context from a value, e.g. an id or a path, that is only formatted when
+    /// rendered. Unlike `.context(format!(..))`, nothing is formatted if the error is handled and
+    /// dropped. This becomes a no-op if the `context` feature is disabled.
+    #[cfg_attr(feature = "location", track_caller)]
+    fn context_display<D: Display + Send + Sync + 'static>(
+        self,
+        value: D,
+    ) -> Result<Self::Okay, ErrorUnion<Self::OutSet>>
+    where
+        Self: Sized,
+    {
+        self.context(ContextSource::display(value))
+    }
+
+    /// Adds an annotation of `kind`, e.g. a help or note. This becomes a no-op if the `context` feature is disabled.
+    fn annotate<C: Into<ContextSource>>(
+        self,
+        kind: FrameKind,
+        context: C,
+    ) -> Result<Self::Okay, ErrorUnion<Self::OutSet>>;
+
+    /// Adds a note, additional information about the error. This becomes a no-op if the `context` feature is disabled.
+    #[cfg_attr(feature = "location", track_caller)]
+    fn note<C: Into<ContextSource>>(self, note: C) -> Result<Self::Okay, ErrorUnion<Self::OutSet>>
+    where
+        Self: Sized,
+    {
+        self.annotate(FrameKind::Note, note)
+    }
+
+    /// Adds a help, a hint on how to fix the error. This becomes a no-op if the `context` feature is disabled.
+    #[cfg_attr(feature = "location", track_caller)]
+    fn help<C: Into<ContextSource>>(self, help: C) -> Result<Self::Okay, ErrorUnion<Self::OutSet>>
+    where
+        Self: Sized,
+    {
+        self.annotate(FrameKind::Help, help)
+    }
+
+    /// Adds a warning, something that may be related to the error. This becomes a no-op if the `context` feature is disabled.
+    #[cfg_attr(feature = "location", track_caller)]
+    fn warning<C: Into<ContextSource>>(
+        self,
+        warning: C,
+    ) -> Result<Self::Okay, ErrorUnion<Self::OutSet>>
+    where
+        Self: Sized,
+    {
+        self.annotate(FrameKind::Warning, warning)
+    }
+
+    /// Adds additional context that is sensitive, e.g. emails, tokens or file paths. It is
+    /// rendered as [`REDACTED`](crate::REDACTED) when redacting. This becomes a no-op if the
+    /// `context` feature is disabled.
+    #[cfg_attr(feature = "location", track_caller)]
+    fn sensitive_context<C: Into<ContextSource>>(
+        self,
+        context: C,
+    ) -> Result<Self::Okay, ErrorUnion<Self::OutSet>>
+    where
+        Self: Sized,
+    {
+        self.context(crate::Secret(context))
+    }
+
+    /// Lazily adds additional context that is sensitive. This becomes a no-op if the `context`
+    /// feature is disabled.
+    #[cfg_attr(feature = "location", track_caller)]
+    fn with_sensitive_context<F, C: Into<ContextSource>>(
+        self,
+        f: F,
+    ) -> Result<Self::Okay, ErrorUnion<Self::OutSet>>
+    where
+        Self: Sized,
+        F: FnOnce() -> C,
+    {
+        self.with_context(|| crate::Secret(f()))
+    }
+
     /// Adds additional context that is user facing. This becomes a no-op if the `context` feature is disabled.
     #[cfg(feature = "user_context")]
     fn user_context<C: Into<ContextSource>>(
@@ -116,6 +452,29 @@ pub trait Context {
     ) -> Result<Self::Okay, ErrorUnion<Self::OutSet>>
     where
         F: FnOnce() -> C;
+
+    /// Attaches a typed value to the context added last, see [`ErrorUnion::attach`]. This becomes
+    /// a no-op if the `context` feature is disabled.
+    fn attach<A: Any + Send + Sync>(self, value: A)
+    -> Result<Self::Okay, ErrorUnion<Self::OutSet>>;
+
+    /// Same as [`Context::with_context`], but the error and context are recorded at `location`
+    /// rather than the caller. Used by the future and stream adapters, which record their caller
+    /// when created. Not part of the public API.
+    #[doc(hidden)]
+    #[cfg(all(feature = "futures", feature = "location"))]
+    fn with_context_at<F, C: Into<ContextSource>>(
+        self,
+        f: F,
+        location: &'static core::panic::Location<'static>,
+    ) -> Result<Self::Okay, ErrorUnion<Self::OutSet>>
+    where
+        Self: Sized,
+        F: FnOnce() -> C,
+    {
+        let _ = location;
+        self.with_context(f)
+    }
 }
 
 impl<T, InSet: TypeSet> Context for Result<T, ErrorUnion<InSet>> {
@@ -151,6 +510,23 @@ impl<T, InSet: TypeSet> Context for Result<T, ErrorUnion<InSet>> {
         return self;
     }
 
+    #[allow(unused_variables)]
+    #[cfg_attr(feature = "location", track_caller)]
+    fn annotate<C: Into<ContextSource>>(
+        self,
+        kind: FrameKind,
+        context: C,
+    ) -> Result<T, ErrorUnion<Self::OutSet>> {
+        // Note: We use match so the call location gets passed through
+        #[cfg(feature = "context")]
+        return match self {
+            Ok(val) => Ok(val),
+            Err(e) => Err(e.annotate(kind, context)),
+        };
+        #[cfg(not(feature = "context"))]
+        return self;
+    }
+
     #[cfg(feature = "user_context")]
     #[allow(unused_variables)]
     #[cfg_attr(feature = "location", track_caller)]
@@ -187,6 +563,28 @@ impl<T, InSet: TypeSet> Context for Result<T, ErrorUnion<InSet>> {
         #[cfg(not(feature = "context"))]
         return self;
     }
+
+    fn attach<A: Any + Send + Sync>(self, value: A) -> Result<T, ErrorUnion<Self::OutSet>> {
+        match self {
+            Ok(val) => Ok(val),
+            Err(e) => Err(e.attach(value)),
+        }
+    }
+
+    #[cfg(all(feature = "futures", feature = "location"))]
+    fn with_context_at<F, C: Into<ContextSource>>(
+        self,
+        f: F,
+        location: &'static core::panic::Location<'static>,
+    ) -> Result<T, ErrorUnion<Self::OutSet>>
+    where
+        F: FnOnce() -> C,
+    {
+        match self {
+            Ok(val) => Ok(val),
+            Err(e) => Err(e.with_context_at(f, location)),
+        }
+    }
 }
 
 impl<T, E: SendSyncError> Context for Result<T, E> {
@@ -236,6 +634,30 @@ impl<T, E: SendSyncError> Context for Result<T, E> {
         };
     }
 
+    #[allow(unused_variables)]
+    #[cfg_attr(feature = "location", track_caller)]
+    fn annotate<C: Into<ContextSource>>(
+        self,
+        kind: FrameKind,
+        context: C,
+    ) -> Result<T, ErrorUnion<Self::OutSet>> {
+        // Note: We use match so the call location gets passed through
+        #[cfg(feature = "context")]
+        return match self {
+            Ok(val) => Ok(val),
+            Err(e) => {
+                let widened: ErrorUnion<Self::OutSet> = ErrorUnion::new(e);
+                Err(widened.annotate(kind, context))
+            }
+        };
+        // Note: We use match so the call location gets passed through
+        #[cfg(not(feature = "context"))]
+        return match self {
+            Ok(val) => Ok(val),
+            Err(e) => Err(ErrorUnion::new(e)),
+        };
+    }
+
     #[cfg(feature = "user_context")]
     #[allow(unused_variables)]
     #[cfg_attr(feature = "location", track_caller)]
@@ -286,6 +708,34 @@ impl<T, E: SendSyncError> Context for Result<T, E> {
             Err(e) => Err(ErrorUnion::new(e)),
         };
     }
+
+    /// The error has no context yet, so this only converts it into an `ErrorUnion`
+    #[cfg_attr(feature = "location", track_caller)]
+    fn attach<A: Any + Send + Sync>(self, _value: A) -> Result<T, ErrorUnion<Self::OutSet>> {
+        // Note: We use match so the call location gets passed through
+        match self {
+            Ok(val) => Ok(val),
+            Err(e) => Err(ErrorUnion::new(e)),
+        }
+    }
+
+    #[cfg(all(feature = "futures", feature = "location"))]
+    fn with_context_at<F, C: Into<ContextSource>>(
+        self,
+        f: F,
+        location: &'static core::panic::Location<'static>,
+    ) -> Result<T, ErrorUnion<Self::OutSet>>
+    where
+        F: FnOnce() -> C,
+    {
+        match self {
+            Ok(val) => Ok(val),
+            Err(e) => {
+                let widened: ErrorUnion<Self::OutSet> = ErrorUnion::new_at(e, location);
+                Err(widened.with_context_at(f, location))
+            }
+        }
+    }
 }
 
 // todo implement when never type is stabilized
@@ -382,6 +832,30 @@ impl<T> Context for Option<T> {
         };
     }
 
+    #[allow(unused_variables)]
+    #[cfg_attr(feature = "location", track_caller)]
+    fn annotate<C: Into<ContextSource>>(
+        self,
+        kind: FrameKind,
+        context: C,
+    ) -> Result<T, ErrorUnion<Self::OutSet>> {
+        // Note: We use match so the call location gets passed through
+        #[cfg(feature = "context")]
+        return match self {
+            Some(val) => Ok(val),
+            None => {
+                let widened: ErrorUnion<Self::OutSet> = ErrorUnion::new(AbsentValueError);
+                Err(widened.annotate(kind, context))
+            }
+        };
+        // Note: We use match so the call location gets passed through
+        #[cfg(not(feature = "context"))]
+        return match self {
+            Some(val) => Ok(val),
+            None => Err(ErrorUnion::new(AbsentValueError)),
+        };
+    }
+
     #[cfg(feature = "user_context")]
     #[allow(unused_variables)]
     #[cfg_attr(feature = "location", track_caller)]
@@ -432,6 +906,34 @@ impl<T> Context for Option<T> {
             None => Err(ErrorUnion::new(AbsentValueError)),
         };
     }
+    /// The error has no context yet, so this only converts `None` into an `ErrorUnion`
+    #[cfg_attr(feature = "location", track_caller)]
+    fn attach<A: Any + Send + Sync>(self, _value: A) -> Result<T, ErrorUnion<Self::OutSet>> {
+        // Note: We use match so the call location gets passed through
+        match self {
+            Some(val) => Ok(val),
+            None => Err(ErrorUnion::new(AbsentValueError)),
+        }
+    }
+
+    #[cfg(all(feature = "futures", feature = "location"))]
+    fn with_context_at<F, C: Into<ContextSource>>(
+        self,
+        f: F,
+        location: &'static core::panic::Location<'static>,
+    ) -> Result<T, ErrorUnion<Self::OutSet>>
+    where
+        F: FnOnce() -> C,
+    {
+        match self {
+            Some(val) => Ok(val),
+            None => {
+                let widened: ErrorUnion<Self::OutSet> =
+                    ErrorUnion::new_at(AbsentValueError, location);
+                Err(widened.with_context_at(f, location))
+            }
+        }
+    }
 }
 
 /// An Error type for unwrapping an `Option` that is `None`, but expected to be `Some`.
@@ -1,5 +1,6 @@
+use alloc::alloc::Layout;
 use alloc::boxed::Box;
-#[cfg(feature = "context")]
+#[cfg(feature = "return_trace")]
 use alloc::vec::Vec;
 use core::any::Any;
 #[cfg(not(feature = "std"))]
@@ -7,14 +8,14 @@ use core::any::TypeId;
 use core::fmt;
 use core::marker::PhantomData;
 use core::mem;
-use core::ops::Deref;
-use core::ptr;
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self, NonNull};
 #[cfg(feature = "std")]
 use std::any::TypeId;
 
-use crate::context::ContextSource;
 #[cfg(feature = "context")]
-use crate::context::ErosContext;
+use crate::context::Frames;
+use crate::context::{ContextFrame, ContextSource, FrameKind};
 use crate::type_set::{
     Contains, DebugFold, DisplayFold, ErrorFold, IsFold, Narrow, SupersetOf, TupleForm, TypeSet,
     write_debug, write_display,
@@ -52,64 +53,240 @@ impl core::error::Error for Box<dyn SendSyncError> {
     }
 }
 
+/// The heap allocation of an [`ErrorUnion`]. The vtable is the first field, so a thin pointer to the
+/// allocation is enough to recover `ErrorUnionInner<dyn SendSyncError>`, see [`InnerBox`].
+#[repr(C)]
 pub(crate) struct ErrorUnionInner<T: ?Sized> {
+    pub(crate) vtable: &'static InnerVTable,
     #[cfg(feature = "backtrace")]
     pub(crate) backtrace: std::backtrace::Backtrace,
     #[cfg(feature = "context")]
-    pub(crate) context: Vec<ErosContext>,
+    pub(crate) context: Frames,
     #[cfg(feature = "location")]
     pub(crate) location: &'static core::panic::Location<'static>,
-    /// Re-boxes the error field into a fresh allocation.
-    /// Stored at construction so the concrete type is still known.
-    pub(crate) into_box_fn: fn(*mut dyn SendSyncError) -> Box<dyn SendSyncError>,
+    #[cfg(feature = "return_trace")]
+    pub(crate) return_trace: Vec<&'static core::panic::Location<'static>>,
+    #[cfg(feature = "span_trace")]
+    pub(crate) span_trace: tracing_error::SpanTrace,
     pub(crate) error: T,
 }
 
-fn make_box<T: SendSyncError>(ptr: *mut dyn SendSyncError) -> Box<dyn SendSyncError> {
-    // SAFETY: caller guarantees ptr points to a live T
-    let value: T = unsafe { ptr::read(ptr as *const dyn SendSyncError as *const T) };
-    Box::new(value)
+/// Functions for the concrete error type, stored at construction so the type is still known
+#[allow(clippy::type_complexity)]
+pub(crate) struct InnerVTable {
+    /// Re-creates the fat pointer from a thin pointer to the allocation
+    to_dyn: unsafe fn(NonNull<&'static InnerVTable>) -> NonNull<ErrorUnionInner<dyn SendSyncError>>,
+    /// Consumes the allocation, moving the error into its own `Box`, see [`into_error_box`]
+    pub(crate) into_error_box:
+        unsafe fn(Box<ErrorUnionInner<dyn SendSyncError>>) -> Box<dyn SendSyncError>,
+    /// The name of the concrete error type
+    type_name: fn() -> &'static str,
 }
 
-impl ErrorUnionInner<dyn SendSyncError> {
+impl InnerVTable {
+    const fn of<T: SendSyncError>() -> &'static InnerVTable {
+        &InnerVTable {
+            to_dyn: to_dyn::<T>,
+            into_error_box: into_error_box::<T>,
+            type_name: core::any::type_name::<T>,
+        }
+    }
+}
+
+unsafe fn to_dyn<T: SendSyncError>(
+    ptr: NonNull<&'static InnerVTable>,
+) -> NonNull<ErrorUnionInner<dyn SendSyncError>> {
+    // SAFETY: caller guarantees ptr points to the start of a live ErrorUnionInner<T>
+    ptr.cast::<ErrorUnionInner<T>>()
+}
+
+/// Drops everything but the error and moves the error into its own `Box`. When the alignment of
+/// `T` is that of the whole allocation, the error is moved to the start of the allocation and the
+/// allocation is shrunk to fit, so the error is not copied into a new allocation. Otherwise, e.g.
+/// for a `u8` error or a zero sized error, a new `Box` is made and the allocation freed.
+///
+/// # Safety
+/// `inner` must hold a `T`
+unsafe fn into_error_box<T: SendSyncError>(
+    inner: Box<ErrorUnionInner<dyn SendSyncError>>,
+) -> Box<dyn SendSyncError> {
+    let layout = Layout::for_value(&*inner);
+    let raw = Box::into_raw(inner) as *mut ErrorUnionInner<T>;
+    unsafe {
+        #[cfg(feature = "backtrace")]
+        ptr::drop_in_place(ptr::addr_of_mut!((*raw).backtrace));
+        #[cfg(feature = "context")]
+        ptr::drop_in_place(ptr::addr_of_mut!((*raw).context));
+        #[cfg(feature = "location")]
+        ptr::drop_in_place(ptr::addr_of_mut!((*raw).location));
+        #[cfg(feature = "return_trace")]
+        ptr::drop_in_place(ptr::addr_of_mut!((*raw).return_trace));
+        #[cfg(feature = "span_trace")]
+        ptr::drop_in_place(ptr::addr_of_mut!((*raw).span_trace));
+
+        let error_ptr = ptr::addr_of_mut!((*raw).error);
+        if mem::size_of::<T>() == 0 || mem::align_of::<T>() != layout.align() {
+            let error = ptr::read(error_ptr);
+            alloc::alloc::dealloc(raw as *mut u8, layout);
+            return Box::new(error);
+        }
+
+        // The header is always non-empty, so the error is never at the start already. `copy`
+        // since the error may overlap its new location.
+        let start = raw as *mut u8;
+        ptr::copy(error_ptr as *const u8, start, mem::size_of::<T>());
+        // Same alignment as `T`, so the shrunk allocation has the layout a `Box<T>` expects
+        let shrunk = alloc::alloc::realloc(start, layout, mem::size_of::<T>());
+        if shrunk.is_null() {
+            alloc::alloc::handle_alloc_error(Layout::new::<T>());
+        }
+        Box::from_raw(shrunk as *mut T)
+    }
+}
+
+/// An owned `Box<ErrorUnionInner<dyn SendSyncError>>` that is a single pointer, since the vtable
+/// is stored in the allocation. The `NonNull` keeps the niche, so `Option<ErrorUnion>` and
+/// `Result<(), ErrorUnion>` are also a single pointer.
+pub(crate) struct InnerBox {
+    ptr: NonNull<&'static InnerVTable>,
+    _pd: PhantomData<ErrorUnionInner<dyn SendSyncError>>,
+}
+
+// SAFETY: `InnerBox` owns an `ErrorUnionInner<dyn SendSyncError>`, which is `Send` and `Sync`
+unsafe impl Send for InnerBox {}
+unsafe impl Sync for InnerBox {}
+
+impl InnerBox {
     #[cfg_attr(feature = "location", track_caller)]
-    pub(crate) fn new<T>(t: T) -> Box<ErrorUnionInner<dyn SendSyncError>>
+    pub(crate) fn new<T>(t: T) -> Self
     where
         T: SendSyncError,
     {
-        Box::new(ErrorUnionInner {
+        InnerBox::new_with(t, false)
+    }
+
+    /// `expected` skips capturing a backtrace and span trace, as do errors registered with
+    /// [`register_expected`](crate::register_expected), see [`ErrorUnion::expected`]
+    #[cfg_attr(feature = "location", track_caller)]
+    #[allow(unused_variables)]
+    pub(crate) fn new_with<T>(t: T, expected: bool) -> Self
+    where
+        T: SendSyncError,
+    {
+        InnerBox::from_box(Box::new(ErrorUnionInner {
+            vtable: InnerVTable::of::<T>(),
             #[cfg(feature = "backtrace")]
-            backtrace: std::backtrace::Backtrace::capture(),
-            #[cfg(feature = "context")]
-            context: Vec::new(),
+            backtrace: crate::backtrace::capture(|| expected || crate::policy::is_expected(&t)),
+            #[cfg(all(feature = "context", not(feature = "scope")))]
+            context: Frames::new(),
+            #[cfg(feature = "scope")]
+            context: crate::scope::snapshot(),
             #[cfg(feature = "location")]
             location: core::panic::Location::caller(),
-            into_box_fn: make_box::<T>,
+            #[cfg(feature = "return_trace")]
+            return_trace: Vec::new(),
+            #[cfg(feature = "span_trace")]
+            span_trace: crate::span_trace::capture(|| expected || crate::policy::is_expected(&t)),
             error: t,
-        })
+        }))
     }
 
     pub(crate) fn new_from_parts<T>(
         t: T,
         #[cfg(feature = "backtrace")] backtrace: std::backtrace::Backtrace,
-        #[cfg(feature = "context")] context: Vec<ErosContext>,
+        #[cfg(feature = "context")] context: Frames,
         #[cfg(feature = "location")] location: &'static core::panic::Location<'static>,
-    ) -> Box<ErrorUnionInner<dyn SendSyncError>>
+        #[cfg(feature = "return_trace")] return_trace: Vec<&'static core::panic::Location<'static>>,
+        #[cfg(feature = "span_trace")] span_trace: tracing_error::SpanTrace,
+    ) -> Self
     where
         T: SendSyncError,
     {
-        Box::new(ErrorUnionInner {
+        InnerBox::from_box(Box::new(ErrorUnionInner {
+            vtable: InnerVTable::of::<T>(),
             #[cfg(feature = "backtrace")]
             backtrace,
             #[cfg(feature = "context")]
             context,
             #[cfg(feature = "location")]
             location,
-            into_box_fn: make_box::<T>,
+            #[cfg(feature = "return_trace")]
+            return_trace,
+            #[cfg(feature = "span_trace")]
+            span_trace,
             error: t,
-        })
+        }))
+    }
+
+    /// Records the caller in the return trace. This becomes a no-op if the `return_trace` feature
+    /// is disabled.
+    #[allow(unused_mut)]
+    #[cfg_attr(feature = "location", track_caller)]
+    fn traced(mut self) -> Self {
+        #[cfg(feature = "return_trace")]
+        self.return_trace.push(core::panic::Location::caller());
+        self
+    }
+
+    fn from_box<T: SendSyncError>(inner: Box<ErrorUnionInner<T>>) -> Self {
+        InnerBox {
+            // SAFETY: `Box::into_raw` is never null
+            ptr: unsafe { NonNull::new_unchecked(Box::into_raw(inner)) }.cast(),
+            _pd: PhantomData,
+        }
+    }
+
+    fn as_dyn(&self) -> NonNull<ErrorUnionInner<dyn SendSyncError>> {
+        // SAFETY: `ptr` always points to the start of a live `ErrorUnionInner`, whose first field is the vtable
+        unsafe { (self.ptr.as_ref().to_dyn)(self.ptr) }
+    }
+
+    /// Converts back into the fat `Box`, e.g. to move the error out
+    pub(crate) fn into_box(self) -> Box<ErrorUnionInner<dyn SendSyncError>> {
+        let this = mem::ManuallyDrop::new(self);
+        // SAFETY: the allocation was created by a `Box` and ownership is transferred
+        unsafe { Box::from_raw(this.as_dyn().as_ptr()) }
+    }
+
+    pub(crate) unsafe fn downcast_error_unchecked<T: 'static>(self) -> T {
+        unsafe { self.into_box().downcast_error_unchecked() }
+    }
+
+    pub(crate) unsafe fn downcast_error_unchecked_with_parts<T: 'static>(
+        self,
+    ) -> ErrorUnionInner<T> {
+        unsafe { self.into_box().downcast_error_unchecked_with_parts() }
+    }
+
+    pub(crate) fn downcast_error<T: 'static>(self) -> Option<T> {
+        self.into_box().downcast_error()
+    }
+}
+
+impl Deref for InnerBox {
+    type Target = ErrorUnionInner<dyn SendSyncError>;
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: `InnerBox` owns the allocation
+        unsafe { self.as_dyn().as_ref() }
+    }
+}
+
+impl DerefMut for InnerBox {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // SAFETY: `InnerBox` uniquely owns the allocation
+        unsafe { self.as_dyn().as_mut() }
+    }
+}
+
+impl Drop for InnerBox {
+    fn drop(&mut self) {
+        // SAFETY: the allocation was created by a `Box` and is not used after this
+        drop(unsafe { Box::from_raw(self.as_dyn().as_ptr()) });
     }
+}
 
+impl ErrorUnionInner<dyn SendSyncError> {
     #[allow(unstable_name_collisions)]
     pub(crate) fn is_error<T: 'static>(&self) -> bool {
         self.error.type_id() == TypeId::of::<T>()
@@ -135,6 +312,10 @@ impl ErrorUnionInner<dyn SendSyncError> {
             ptr::drop_in_place(ptr::addr_of_mut!((*raw_container).context));
             #[cfg(feature = "location")]
             ptr::drop_in_place(ptr::addr_of_mut!((*raw_container).location));
+            #[cfg(feature = "return_trace")]
+            ptr::drop_in_place(ptr::addr_of_mut!((*raw_container).return_trace));
+            #[cfg(feature = "span_trace")]
+            ptr::drop_in_place(ptr::addr_of_mut!((*raw_container).span_trace));
 
             // Deallocate the Box allocation itself.
             // We reconstruct a Box containing uninitialized/dead data, but wrapped in
@@ -169,8 +350,12 @@ impl ErrorUnionInner<dyn SendSyncError> {
             let context = ptr::read(ptr::addr_of!((*raw_container).context));
             #[cfg(feature = "location")]
             let location = ptr::read(ptr::addr_of!((*raw_container).location));
+            #[cfg(feature = "return_trace")]
+            let return_trace = ptr::read(ptr::addr_of!((*raw_container).return_trace));
+            #[cfg(feature = "span_trace")]
+            let span_trace = ptr::read(ptr::addr_of!((*raw_container).span_trace));
 
-            let into_box_fn = ptr::read(ptr::addr_of!((*raw_container).into_box_fn));
+            let vtable = ptr::read(ptr::addr_of!((*raw_container).vtable));
 
             // Deallocate the Box allocation itself.
             // We reconstruct a Box containing uninitialized/dead data, but wrapped in
@@ -180,13 +365,17 @@ impl ErrorUnionInner<dyn SendSyncError> {
                 Box::from_raw(raw_container as *mut mem::ManuallyDrop<Self>);
 
             ErrorUnionInner {
+                vtable,
                 #[cfg(feature = "backtrace")]
                 backtrace,
                 #[cfg(feature = "context")]
                 context,
                 #[cfg(feature = "location")]
                 location,
-                into_box_fn,
+                #[cfg(feature = "return_trace")]
+                return_trace,
+                #[cfg(feature = "span_trace")]
+                span_trace,
                 error: downcasted_value,
             }
         }
@@ -247,7 +436,7 @@ impl ErrorUnionInner<dyn SendSyncError> {
 /// flags enabled. This may include `Backtrace` and/or `Location`. Context can be added throughout
 /// the call stack.
 pub struct ErrorUnion<E: TypeSet = AnyError> {
-    pub(crate) inner: Box<ErrorUnionInner<dyn SendSyncError>>,
+    pub(crate) inner: InnerBox,
     pub(crate) _pd: PhantomData<E>,
 }
 
@@ -273,6 +462,10 @@ impl fmt::Debug for ErrorUnion<AnyError> {
             &self.inner.backtrace,
             #[cfg(feature = "location")]
             self.inner.location,
+            #[cfg(feature = "return_trace")]
+            &self.inner.return_trace,
+            #[cfg(feature = "span_trace")]
+            &self.inner.span_trace,
         )
     }
 }
@@ -292,6 +485,10 @@ where
             &self.inner.backtrace,
             #[cfg(feature = "location")]
             self.inner.location,
+            #[cfg(feature = "return_trace")]
+            &self.inner.return_trace,
+            #[cfg(feature = "span_trace")]
+            &self.inner.span_trace,
         )?;
         Ok(())
     }
@@ -299,7 +496,12 @@ where
 
 impl fmt::Display for ErrorUnion<AnyError> {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write_display(&self.inner.error, formatter)
+        write_display(
+            &self.inner.error,
+            formatter,
+            #[cfg(feature = "context")]
+            &self.inner.context,
+        )
     }
 }
 
@@ -309,7 +511,12 @@ where
     E::Variants: fmt::Display + DisplayFold,
 {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
-        E::Variants::display_fold(&self.inner.error as &dyn Any, formatter)?;
+        E::Variants::display_fold(
+            &self.inner.error,
+            formatter,
+            #[cfg(feature = "context")]
+            &self.inner.context,
+        )?;
         Ok(())
     }
 }
@@ -346,7 +553,45 @@ impl ErrorUnion {
         OutSet::Variants: Contains<T, Index>,
     {
         ErrorUnion {
-            inner: ErrorUnionInner::new(t),
+            inner: InnerBox::new(t),
+            _pd: PhantomData,
+        }
+    }
+
+    /// Same as [`ErrorUnion::new`], but recorded at `location` rather than the caller. Used where
+    /// the caller is not known when the error is created, e.g. in the `poll` of a future adapter.
+    #[cfg(all(feature = "futures", feature = "location"))]
+    pub(crate) fn new_at<T, OutSet, Index>(
+        t: T,
+        location: &'static core::panic::Location<'static>,
+    ) -> ErrorUnion<OutSet>
+    where
+        T: SendSyncError,
+        OutSet: TypeSet,
+        OutSet::Variants: Contains<T, Index>,
+    {
+        let mut error = ErrorUnion::new(t);
+        error.inner.location = location;
+        error
+    }
+
+    /// Create a new `ErrorUnion` for an expected error, e.g. a `NotFound` that is narrowed and
+    /// handled right away, so no backtrace or span trace is captured, unless the
+    /// [`BacktracePolicy`](crate::BacktracePolicy) is `Force`. For errors that are always
+    /// expected, register the type with [`register_expected`](crate::register_expected) instead,
+    /// which also covers `?` and [`ErrorUnion::new`].
+    /// [`ErrorUnion::force_backtrace`] can still capture one later, if the error ends up being
+    /// propagated.
+    #[cfg(any(feature = "backtrace", feature = "span_trace"))]
+    #[cfg_attr(feature = "location", track_caller)]
+    pub fn expected<T, OutSet, Index>(t: T) -> ErrorUnion<OutSet>
+    where
+        T: SendSyncError,
+        OutSet: TypeSet,
+        OutSet::Variants: Contains<T, Index>,
+    {
+        ErrorUnion {
+            inner: InnerBox::new_with(t, true),
             _pd: PhantomData,
         }
     }
@@ -355,8 +600,10 @@ impl ErrorUnion {
     pub(crate) fn new_from_parts<T, OutSet, Index>(
         t: T,
         #[cfg(feature = "backtrace")] backtrace: std::backtrace::Backtrace,
-        #[cfg(feature = "context")] context: Vec<ErosContext>,
+        #[cfg(feature = "context")] context: Frames,
         #[cfg(feature = "location")] location: &'static core::panic::Location<'static>,
+        #[cfg(feature = "return_trace")] return_trace: Vec<&'static core::panic::Location<'static>>,
+        #[cfg(feature = "span_trace")] span_trace: tracing_error::SpanTrace,
     ) -> ErrorUnion<OutSet>
     where
         T: SendSyncError,
@@ -364,7 +611,7 @@ impl ErrorUnion {
         OutSet::Variants: Contains<T, Index>,
     {
         ErrorUnion {
-            inner: ErrorUnionInner::new_from_parts(
+            inner: InnerBox::new_from_parts(
                 t,
                 #[cfg(feature = "backtrace")]
                 backtrace,
@@ -372,6 +619,10 @@ impl ErrorUnion {
                 context,
                 #[cfg(feature = "location")]
                 location,
+                #[cfg(feature = "return_trace")]
+                return_trace,
+                #[cfg(feature = "span_trace")]
+                span_trace,
             ),
             _pd: PhantomData,
         }
@@ -432,7 +683,9 @@ where
     /// Creates a `Box<dyn SendSyncError>` error from this [`crate::ErrorUnion`]. This is used since
     /// [`crate::ErrorUnion`] cannot implement [`core::error::Error`] directly, otherwise trait implementations
     /// that require this bounds would conflict. To convert back into a [`crate::ErrorUnion`],
-    /// [`crate::ErrorUnion::from_dyn_error`] must be used.
+    /// [`crate::ErrorUnion::from_dyn_error`] must be used. Unlike [`ErrorUnion::into_inner`], this
+    /// makes a new, pointer sized, allocation for the wrapper, since the wrapper has to be a
+    /// single type for `from_dyn_error` to recognize, whatever the error inside.
     pub fn into_dyn_error(self) -> Box<dyn SendSyncError> {
         Box::new(ErrorUnionErrorWrapper(self)) as Box<dyn SendSyncError>
     }
@@ -459,6 +712,7 @@ where
     /// if that fails, return a `ErrorUnion` which does not contain that
     /// type as one of its possible variants.
     #[allow(clippy::type_complexity)]
+    #[cfg_attr(feature = "location", track_caller)]
     pub fn narrow<Target, Index>(
         self,
     ) -> Result<
@@ -473,7 +727,7 @@ where
             Ok(unsafe { self.inner.downcast_error_unchecked::<Target>() })
         } else {
             Err(ErrorUnion {
-                inner: self.inner,
+                inner: self.inner.traced(),
                 _pd: PhantomData,
             })
         }
@@ -482,13 +736,14 @@ where
     /// Turns the `ErrorUnion` into a `ErrorUnion` with a set of variants
     /// which is a superset of the current one. This may also be
     /// the same set of variants, but in a different order.
+    #[cfg_attr(feature = "location", track_caller)]
     pub fn widen<Other, Index>(self) -> ErrorUnion<Other>
     where
         Other: TypeSet,
         Other::Variants: SupersetOf<E::Variants, Index>,
     {
         ErrorUnion {
-            inner: self.inner,
+            inner: self.inner.traced(),
             _pd: PhantomData,
         }
     }
@@ -542,10 +797,71 @@ where
         &self.inner.backtrace
     }
 
+    /// Records the caller as a point the error was propagated through, rendered in the return
+    /// trace. `widen`, `narrow`, `into_union` and `into_dyn_union` record one as well. This
+    /// becomes a no-op if the `return_trace` feature is disabled.
+    #[cfg_attr(feature = "location", track_caller)]
+    pub fn trace(self) -> Self {
+        ErrorUnion {
+            inner: self.inner.traced(),
+            _pd: PhantomData,
+        }
+    }
+
+    /// The points the error was propagated through, oldest first. See [`ErrorUnion::trace`].
+    #[cfg(feature = "return_trace")]
+    pub fn return_trace(&self) -> &[&'static core::panic::Location<'static>] {
+        &self.inner.return_trace
+    }
+
+    /// The spans that were active when the error was created, if captured, see
+    /// [`SpanTracePolicy`](crate::SpanTracePolicy). Spans are only recorded if the subscriber has
+    /// a `tracing_error::ErrorLayer`.
+    #[cfg(feature = "span_trace")]
+    pub fn span_trace(&self) -> &tracing_error::SpanTrace {
+        &self.inner.span_trace
+    }
+
+    /// Captures a span trace at this point, if none was captured when the error was created, e.g.
+    /// because of the [`SpanTracePolicy`](crate::SpanTracePolicy). This becomes a no-op if the
+    /// `span_trace` feature is disabled.
+    #[allow(unused_mut)]
+    pub fn force_span_trace(mut self) -> Self {
+        #[cfg(feature = "span_trace")]
+        if self.inner.span_trace.status() != tracing_error::SpanTraceStatus::CAPTURED {
+            self.inner.span_trace = tracing_error::SpanTrace::capture();
+        }
+        self
+    }
+
+    /// Captures a backtrace at this point, if none was captured when the error was created, e.g.
+    /// because of the [`BacktracePolicy`](crate::BacktracePolicy). Useful where an expected error
+    /// starts to propagate. This becomes a no-op if the `backtrace` feature is disabled.
+    #[allow(unused_mut)]
+    pub fn force_backtrace(mut self) -> Self {
+        #[cfg(feature = "backtrace")]
+        if self.inner.backtrace.status() != std::backtrace::BacktraceStatus::Captured {
+            self.inner.backtrace = std::backtrace::Backtrace::force_capture();
+        }
+        self
+    }
+
     pub fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
         self.inner.error.source()
     }
 
+    /// The name of the type of the inner underlying error, e.g. `std::io::error::Error`. Like
+    /// [`core::any::type_name`], this is meant for diagnostics, e.g. as a field of a log event.
+    pub fn type_name(&self) -> &'static str {
+        (self.inner.vtable.type_name)()
+    }
+
+    /// Where the error was created
+    #[cfg(feature = "location")]
+    pub fn location(&self) -> &'static core::panic::Location<'static> {
+        self.inner.location
+    }
+
     /// Gets a reference to the inner underlying error
     pub fn inner_ref(&self) -> &dyn SendSyncError {
         &self.inner.error
@@ -556,32 +872,16 @@ where
         &mut self.inner.error
     }
 
-    /// Into the inner underlying error
+    /// Into the inner underlying error. The allocation of the `ErrorUnion` is reused for the
+    /// returned `Box` when the error is at least pointer aligned, which is the case for most errors.
     pub fn into_inner(self) -> Box<dyn SendSyncError> {
-        let raw = Box::into_raw(self.inner);
-        unsafe {
-            let into_box_fn = (*raw).into_box_fn;
-            let error_ptr = ptr::addr_of_mut!((*raw).error);
-
-            let boxed = (into_box_fn)(error_ptr);
-
-            // Drop remaining fields, free the allocation (same pattern as downcast_error_unchecked)
-            #[cfg(feature = "backtrace")]
-            ptr::drop_in_place(ptr::addr_of_mut!((*raw).backtrace));
-            #[cfg(feature = "context")]
-            ptr::drop_in_place(ptr::addr_of_mut!((*raw).context));
-            #[cfg(feature = "location")]
-            ptr::drop_in_place(ptr::addr_of_mut!((*raw).location));
-
-            let _dead: Box<mem::ManuallyDrop<ErrorUnionInner<dyn SendSyncError>>> =
-                Box::from_raw(raw as *mut _);
-
-            boxed
-        }
+        let inner = self.inner.into_box();
+        // SAFETY: the vtable is for the type of the error
+        unsafe { (inner.vtable.into_error_box)(inner) }
     }
 
     /// Returns the latest error.
-    /// This takes into consideration errors added as context
+    /// This takes into consideration errors added as context, but not sensitive ones
     pub fn latest_error(&self) -> &dyn SendSyncError {
         #[cfg(feature = "context")]
         for context in self.inner.context.iter().rev() {
@@ -623,12 +923,82 @@ where
     #[cfg_attr(feature = "location", track_caller)]
     pub fn context<C: Into<ContextSource>>(mut self, context: C) -> Self {
         #[cfg(feature = "context")]
-        self.inner
-            .context
-            .push(crate::context::ErosContext::new(context.into()));
+        crate::context::push_frame(&mut self.inner.context, ContextFrame::new(context.into()));
+        self
+    }
+
+    /// Adds additional context from a value, e.g. an id or a path, that is only formatted when
+    /// rendered. This becomes a no-op if the `context` feature is disabled.
+    #[allow(unused_mut)]
+    #[allow(unused_variables)]
+    #[cfg_attr(feature = "location", track_caller)]
+    pub fn context_display<D: core::fmt::Display + Send + Sync + 'static>(
+        mut self,
+        value: D,
+    ) -> Self {
+        #[cfg(feature = "context")]
+        crate::context::push_frame(
+            &mut self.inner.context,
+            ContextFrame::new(ContextSource::display(value)),
+        );
         self
     }
 
+    /// Adds an annotation of `kind`, e.g. a help or note. This becomes a no-op if the `context` feature is disabled.
+    #[allow(unused_mut)]
+    #[allow(unused_variables)]
+    #[cfg_attr(feature = "location", track_caller)]
+    pub fn annotate<C: Into<ContextSource>>(mut self, kind: FrameKind, context: C) -> Self {
+        #[cfg(feature = "context")]
+        crate::context::push_frame(
+            &mut self.inner.context,
+            ContextFrame::new_annotation(kind, context.into()),
+        );
+        self
+    }
+
+    /// Adds a note, additional information about the error. This becomes a no-op if the `context` feature is disabled.
+    #[cfg_attr(feature = "location", track_caller)]
+    pub fn note<C: Into<ContextSource>>(self, note: C) -> Self {
+        self.annotate(FrameKind::Note, note)
+    }
+
+    /// Adds a help, a hint on how to fix the error. This becomes a no-op if the `context` feature is disabled.
+    #[cfg_attr(feature = "location", track_caller)]
+    pub fn help<C: Into<ContextSource>>(self, help: C) -> Self {
+        self.annotate(FrameKind::Help, help)
+    }
+
+    /// Adds a warning, something that may be related to the error. This becomes a no-op if the `context` feature is disabled.
+    #[cfg_attr(feature = "location", track_caller)]
+    pub fn warning<C: Into<ContextSource>>(self, warning: C) -> Self {
+        self.annotate(FrameKind::Warning, warning)
+    }
+
+    /// Iterates over the annotations of `kind`, in the order they were added.
+    /// e.g. `error.annotations(FrameKind::Help)` to show hints in a UI.
+    pub fn annotations(&self, kind: FrameKind) -> impl Iterator<Item = &ContextSource> {
+        self.frames()
+            .filter(move |frame| frame.kind == kind)
+            .map(|frame| &frame.context)
+    }
+
+    /// Adds additional context that is sensitive, e.g. emails, tokens or file paths. It is rendered
+    /// as [`REDACTED`](crate::REDACTED) when redacting. This becomes a no-op if the `context` feature is disabled.
+    #[cfg_attr(feature = "location", track_caller)]
+    pub fn sensitive_context<C: Into<ContextSource>>(self, context: C) -> Self {
+        self.context(crate::Secret(context))
+    }
+
+    /// Lazily adds additional context that is sensitive. This becomes a no-op if the `context` feature is disabled.
+    #[cfg_attr(feature = "location", track_caller)]
+    pub fn with_sensitive_context<F, C: Into<ContextSource>>(self, f: F) -> Self
+    where
+        F: FnOnce() -> C,
+    {
+        self.with_context(|| crate::Secret(f()))
+    }
+
     /// Adds additional context that is user facing. This becomes a no-op if the `context` feature is disabled.
     #[cfg(feature = "user_context")]
     #[allow(unused_mut)]
@@ -636,9 +1006,26 @@ where
     #[cfg_attr(feature = "location", track_caller)]
     pub fn user_context<C: Into<ContextSource>>(mut self, context: C) -> Self {
         #[cfg(feature = "context")]
-        self.inner
-            .context
-            .push(crate::context::ErosContext::new_user_facing(context.into()));
+        crate::context::push_frame(
+            &mut self.inner.context,
+            ContextFrame::new_user_facing(context.into()),
+        );
+        self
+    }
+
+    /// Adds an annotation of `kind` that is user facing, so it is included in
+    /// [`ErrorUnion::user_report`]. This becomes a no-op if the `context` feature is disabled.
+    #[cfg(feature = "user_context")]
+    #[allow(unused_mut)]
+    #[allow(unused_variables)]
+    #[cfg_attr(feature = "location", track_caller)]
+    pub fn user_annotate<C: Into<ContextSource>>(mut self, kind: FrameKind, context: C) -> Self {
+        #[cfg(feature = "context")]
+        {
+            let mut frame = ContextFrame::new_annotation(kind, context.into());
+            frame.is_user_facing = true;
+            crate::context::push_frame(&mut self.inner.context, frame);
+        }
         self
     }
 
@@ -651,9 +1038,28 @@ where
         F: FnOnce() -> C,
     {
         #[cfg(feature = "context")]
-        self.inner
-            .context
-            .push(crate::context::ErosContext::new(f().into()));
+        crate::context::push_frame(&mut self.inner.context, ContextFrame::new(f().into()));
+        self
+    }
+
+    /// Same as [`ErrorUnion::with_context`], but recorded at `location` rather than the caller
+    #[cfg(all(feature = "futures", feature = "location"))]
+    #[allow(unused_mut)]
+    #[allow(unused_variables)]
+    pub(crate) fn with_context_at<F, C: Into<ContextSource>>(
+        mut self,
+        f: F,
+        location: &'static core::panic::Location<'static>,
+    ) -> Self
+    where
+        F: FnOnce() -> C,
+    {
+        #[cfg(feature = "context")]
+        {
+            let mut frame = ContextFrame::new(f().into());
+            frame.location = location;
+            crate::context::push_frame(&mut self.inner.context, frame);
+        }
         self
     }
 
@@ -667,11 +1073,68 @@ where
         F: FnOnce() -> C,
     {
         #[cfg(feature = "context")]
-        self.inner
-            .context
-            .push(crate::context::ErosContext::new_user_facing(f().into()));
+        crate::context::push_frame(
+            &mut self.inner.context,
+            ContextFrame::new_user_facing(f().into()),
+        );
         self
     }
+
+    /// Iterates over the context frames, in the order they were added.
+    /// This is always empty if the `context` feature is disabled.
+    pub fn frames(&self) -> impl Iterator<Item = &ContextFrame> {
+        #[cfg(feature = "context")]
+        return self.inner.context.iter();
+        #[cfg(not(feature = "context"))]
+        return core::iter::empty();
+    }
+
+    /// Mutably iterates over the context frames, in the order they were added.
+    /// This is always empty if the `context` feature is disabled.
+    pub fn frames_mut(&mut self) -> impl Iterator<Item = &mut ContextFrame> {
+        #[cfg(feature = "context")]
+        return self.inner.context.iter_mut();
+        #[cfg(not(feature = "context"))]
+        return core::iter::empty();
+    }
+
+    /// Retains only the context frames for which `f` returns true.
+    /// Useful for pruning context before an error crosses a trust boundary.
+    #[allow(unused_mut)]
+    #[allow(unused_variables)]
+    pub fn retain_frames<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&ContextFrame) -> bool,
+    {
+        #[cfg(feature = "context")]
+        self.inner.context.retain(|frame| f(frame));
+    }
+
+    /// Attaches a typed value to the context added last, e.g.
+    /// `.context("loading user").attach(KeyValue::new("user_id", id))`, see
+    /// [`ContextFrame::attach`]. Does nothing if no context was added. This becomes a no-op if the
+    /// `context` feature is disabled.
+    #[allow(unused_mut)]
+    #[allow(unused_variables)]
+    pub fn attach<T: Any + Send + Sync>(mut self, value: T) -> Self {
+        #[cfg(feature = "context")]
+        if let Some(frame) = crate::context::last_pushed(&mut self.inner.context) {
+            frame.attach(value);
+        }
+        self
+    }
+
+    /// The values of type `T` attached to any context frame, in the order the frames were added.
+    /// See [`ContextFrame::attach`].
+    pub fn attachments<T: Any>(&self) -> impl Iterator<Item = &T> {
+        self.frames().flat_map(ContextFrame::attachments)
+    }
+
+    /// Removes all context frames
+    pub fn clear_context(&mut self) {
+        #[cfg(feature = "context")]
+        self.inner.context.clear();
+    }
 }
 
 impl<A: 'static> AsRef<A> for ErrorUnion<(A,)> {
@@ -702,7 +1165,7 @@ impl<A: 'static> ErrorUnion<(A,)> {
         // SAFETY: We know that the inner error is only of type A, so we can safely downcast it
         let inner = unsafe { self.inner.downcast_error_unchecked_with_parts::<A>() };
         ErrorUnion {
-            inner: ErrorUnionInner::new_from_parts(
+            inner: InnerBox::new_from_parts(
                 f(inner.error),
                 #[cfg(feature = "backtrace")]
                 inner.backtrace,
@@ -710,6 +1173,10 @@ impl<A: 'static> ErrorUnion<(A,)> {
                 inner.context,
                 #[cfg(feature = "location")]
                 inner.location,
+                #[cfg(feature = "return_trace")]
+                inner.return_trace,
+                #[cfg(feature = "span_trace")]
+                inner.span_trace,
             ),
             _pd: PhantomData,
         }
@@ -718,11 +1185,20 @@ impl<A: 'static> ErrorUnion<(A,)> {
 
 //************************************************************************//
 
-/// Run widen and narrow directly on Results with ErrorUnions
+/// Run widen, narrow and trace directly on Results with ErrorUnions
 pub trait ReshapeUnion<S, E>
 where
     E: TypeSet,
 {
+    /// Records the caller in the return trace of the `ErrorUnion`, if any.
+    /// See [`ErrorUnion::trace`].
+    fn trace(self) -> Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+
     /// Turns the `ErrorUnion` into a `ErrorUnion` with a set of variants
     /// which is a superset of the current one. This may also be
     /// the same set of variants, but in a different order.
@@ -753,14 +1229,27 @@ impl<S, E> ReshapeUnion<S, E> for Result<S, ErrorUnion<E>>
 where
     E: TypeSet,
 {
+    #[cfg_attr(feature = "location", track_caller)]
+    fn trace(self) -> Self {
+        match self {
+            Ok(value) => Ok(value),
+            Err(err) => Err(err.trace()),
+        }
+    }
+
+    #[cfg_attr(feature = "location", track_caller)]
     fn widen<Other, Index>(self) -> Result<S, ErrorUnion<Other>>
     where
         Other: TypeSet,
         Other::Variants: SupersetOf<E::Variants, Index>,
     {
-        self.map_err(|e| e.widen())
+        match self {
+            Ok(value) => Ok(value),
+            Err(err) => Err(err.widen()),
+        }
     }
 
+    #[cfg_attr(feature = "location", track_caller)]
     fn narrow<Target, Index>(
         self,
     ) -> Result<
@@ -804,7 +1293,7 @@ impl<S, F: SendSyncError> IntoUnion<S, F> for Result<S, F> {
         // Note: We use match so the call location gets passed through
         match self {
             Ok(value) => Ok(value),
-            Err(err) => Err(ErrorUnion::new(err)),
+            Err(err) => Err(ErrorUnion::new::<F, Other, Index>(err)),
         }
     }
 }
@@ -819,14 +1308,18 @@ impl<S, F: SendSyncError> IntoDynUnion<S> for Result<S, F> {
     fn into_dyn_union(self) -> Result<S, ErrorUnion> {
         match self {
             Ok(value) => Ok(value),
-            Err(err) => Err(ErrorUnion::new(err)),
+            Err(err) => Err(ErrorUnion::new::<F, AnyError, _>(err)),
         }
     }
 }
 
 impl<S, E: TypeSet> IntoDynUnion<S> for Result<S, ErrorUnion<E>> {
+    #[cfg_attr(feature = "location", track_caller)]
     fn into_dyn_union(self) -> Result<S, ErrorUnion> {
-        self.map_err(|e| ErrorUnion::erase(e))
+        match self {
+            Ok(value) => Ok(value),
+            Err(err) => Err(ErrorUnion::erase(err).trace()),
+        }
     }
 }
 
@@ -940,10 +1433,16 @@ impl ErrorUnion {
             AnyhowError(error),
             #[cfg(feature = "backtrace")]
             std::backtrace::Backtrace::disabled(),
-            #[cfg(feature = "context")]
-            Vec::new(),
+            #[cfg(all(feature = "context", not(feature = "scope")))]
+            Frames::new(),
+            #[cfg(feature = "scope")]
+            crate::scope::snapshot(),
             #[cfg(feature = "location")]
             core::panic::Location::caller(),
+            #[cfg(feature = "return_trace")]
+            Vec::new(),
+            #[cfg(feature = "span_trace")]
+            crate::span_trace::capture(|| false),
         )
     }
 
@@ -953,10 +1452,16 @@ impl ErrorUnion {
             AnyhowErrorArc(error),
             #[cfg(feature = "backtrace")]
             std::backtrace::Backtrace::disabled(),
-            #[cfg(feature = "context")]
-            Vec::new(),
+            #[cfg(all(feature = "context", not(feature = "scope")))]
+            Frames::new(),
+            #[cfg(feature = "scope")]
+            crate::scope::snapshot(),
             #[cfg(feature = "location")]
             core::panic::Location::caller(),
+            #[cfg(feature = "return_trace")]
+            Vec::new(),
+            #[cfg(feature = "span_trace")]
+            crate::span_trace::capture(|| false),
         )
     }
 }
@@ -1010,9 +1515,44 @@ mod tests {
     }
     impl std::error::Error for BarError {}
 
+    #[test]
+    fn error_union_is_a_single_pointer() {
+        const WORD: usize = mem::size_of::<usize>();
+        assert_eq!(mem::size_of::<ErrorUnion>(), WORD);
+        assert_eq!(mem::size_of::<ErrorUnion<(FooError, BarError)>>(), WORD);
+        assert_eq!(mem::size_of::<Option<ErrorUnion>>(), WORD);
+        assert_eq!(mem::size_of::<Result<(), ErrorUnion>>(), WORD);
+        assert_eq!(mem::size_of::<Result<(), ErrorUnion<(FooError,)>>>(), WORD);
+    }
+
+    #[test]
+    fn thin_pointer_roundtrips_over_aligned_error() {
+        #[derive(Debug, PartialEq)]
+        #[repr(align(64))]
+        struct AlignedError(u8);
+        impl fmt::Display for AlignedError {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "AlignedError({})", self.0)
+            }
+        }
+        impl std::error::Error for AlignedError {}
+
+        let mut union: ErrorUnion<(AlignedError, FooError)> = ErrorUnion::new(AlignedError(7));
+        assert_eq!(union.to_string(), "AlignedError(7)");
+        union.downcast_inner_mut::<AlignedError>().unwrap().0 = 8;
+        assert_eq!(
+            union.inner_ref() as *const dyn SendSyncError as *const u8 as usize % 64,
+            0
+        );
+        assert_eq!(
+            union.downcast_inner::<AlignedError>(),
+            Some(AlignedError(8))
+        );
+    }
+
     #[test]
     fn downcast_error_unchecked_correct_type_recovers_value() {
-        let inner = ErrorUnionInner::new(FooError("hello".into()));
+        let inner = InnerBox::new(FooError("hello".into()));
         assert!(inner.is_error::<FooError>());
         let recovered: FooError = unsafe { inner.downcast_error_unchecked() };
         assert_eq!(recovered, FooError("hello".into()));
@@ -1031,7 +1571,7 @@ mod tests {
         }
         impl std::error::Error for VecError {}
 
-        let inner = ErrorUnionInner::new(VecError(payload.clone()));
+        let inner = InnerBox::new(VecError(payload.clone()));
         let recovered: VecError = unsafe { inner.downcast_error_unchecked() };
         assert_eq!(recovered.0, payload);
     }
@@ -1039,13 +1579,13 @@ mod tests {
     #[test]
     #[should_panic]
     fn downcast_error_panics_on_wrong_type() {
-        let inner = ErrorUnionInner::new(FooError("oops".into()));
+        let inner = InnerBox::new(FooError("oops".into()));
         inner.downcast_error::<BarError>().unwrap(); // should panic
     }
 
     #[test]
     fn downcast_error_unchecked_with_parts_preserves_context() {
-        let inner = ErrorUnionInner::new(FooError("ctx".into()));
+        let inner = InnerBox::new(FooError("ctx".into()));
 
         let mut union: ErrorUnion<(FooError,)> = ErrorUnion {
             inner,
@@ -1057,11 +1597,11 @@ mod tests {
             union
                 .inner
                 .context
-                .push(ErosContext::new("step one".into()));
+                .push(ContextFrame::new("step one".into()));
             union
                 .inner
                 .context
-                .push(ErosContext::new("step two".into()));
+                .push(ContextFrame::new("step two".into()));
         }
 
         let parts: ErrorUnionInner<FooError> =
@@ -1075,7 +1615,7 @@ mod tests {
 
     #[test]
     fn downcast_error_unchecked_with_parts_correct_error_value() {
-        let inner = ErrorUnionInner::new(BarError(42));
+        let inner = InnerBox::new(BarError(42));
         let parts: ErrorUnionInner<BarError> =
             unsafe { inner.downcast_error_unchecked_with_parts() };
         assert_eq!(parts.error, BarError(42));
@@ -1234,6 +1774,215 @@ mod tests {
         // `dyn_err` drops here — Miri will catch any double-free or leak.
     }
 
+    #[derive(Debug)]
+    struct DropCounter(&'static std::sync::atomic::AtomicUsize);
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn into_inner_in_place_drops_context_and_keeps_error() {
+        static DROPS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+        #[derive(Debug)]
+        struct CountedError(String, #[allow(dead_code)] DropCounter);
+        impl fmt::Display for CountedError {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+        impl std::error::Error for CountedError {}
+
+        #[allow(unused_mut)]
+        let mut union: ErrorUnion<(CountedError,)> =
+            ErrorUnion::new(CountedError("counted".into(), DropCounter(&DROPS)));
+        #[cfg(feature = "context")]
+        {
+            union = union
+                .context(String::from("heap context"))
+                .context("one")
+                .context("two")
+                .context("spilled");
+        }
+        let dyn_err = union.into_inner();
+        assert_eq!(DROPS.load(std::sync::atomic::Ordering::Relaxed), 0);
+        assert_eq!(dyn_err.to_string(), "counted");
+        drop(dyn_err);
+        assert_eq!(DROPS.load(std::sync::atomic::Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn into_inner_with_any_alignment_and_size() {
+        #[derive(Debug, PartialEq)]
+        struct ByteError(u8);
+        impl fmt::Display for ByteError {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "ByteError({})", self.0)
+            }
+        }
+        impl std::error::Error for ByteError {}
+
+        #[derive(Debug, PartialEq)]
+        struct UnitError;
+        impl fmt::Display for UnitError {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("UnitError")
+            }
+        }
+        impl std::error::Error for UnitError {}
+
+        #[derive(Debug, PartialEq)]
+        #[repr(align(64))]
+        struct AlignedError([u64; 9]);
+        impl fmt::Display for AlignedError {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "AlignedError({})", self.0[8])
+            }
+        }
+        impl std::error::Error for AlignedError {}
+
+        let union: ErrorUnion<(ByteError, UnitError, AlignedError)> = ErrorUnion::new(ByteError(3));
+        let error = union.into_inner();
+        assert_eq!((&*error as &dyn Any).downcast_ref(), Some(&ByteError(3)));
+
+        let union: ErrorUnion<(ByteError, UnitError, AlignedError)> = ErrorUnion::new(UnitError);
+        let error = union.into_inner();
+        assert_eq!((&*error as &dyn Any).downcast_ref(), Some(&UnitError));
+
+        let union: ErrorUnion<(ByteError, UnitError, AlignedError)> =
+            ErrorUnion::new(AlignedError([7; 9]));
+        let error = union.into_inner();
+        assert_eq!(
+            &*error as *const dyn SendSyncError as *const u8 as usize % 64,
+            0
+        );
+        assert_eq!(
+            (&*error as &dyn Any).downcast_ref(),
+            Some(&AlignedError([7; 9]))
+        );
+    }
+
+    // The tests below walk every branch of `into_error_box` with context and heap owning errors,
+    // so `cargo +nightly miri test --lib error_union` checks the copy, `realloc`, and drops.
+
+    #[test]
+    fn into_inner_over_aligned_heap_error_with_context() {
+        #[derive(Debug, PartialEq)]
+        #[repr(align(64))]
+        struct AlignedVecError(Vec<u8>);
+        impl fmt::Display for AlignedVecError {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{:?}", self.0)
+            }
+        }
+        impl std::error::Error for AlignedVecError {}
+
+        #[allow(unused_mut)]
+        let mut union: ErrorUnion<(AlignedVecError, FooError)> =
+            ErrorUnion::new(AlignedVecError(vec![1, 2, 3]));
+        #[cfg(feature = "context")]
+        {
+            union = union
+                .context(String::from("heap context"))
+                .context("one")
+                .context("two")
+                .context("spilled");
+        }
+        let error = union.into_inner();
+        assert_eq!(
+            &*error as *const dyn SendSyncError as *const u8 as usize % 64,
+            0
+        );
+        assert_eq!(
+            (&*error as &dyn Any).downcast_ref(),
+            Some(&AlignedVecError(vec![1, 2, 3]))
+        );
+    }
+
+    #[test]
+    fn into_inner_zero_sized_errors_with_context() {
+        #[derive(Debug, PartialEq)]
+        struct UnitError;
+        impl fmt::Display for UnitError {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("UnitError")
+            }
+        }
+        impl std::error::Error for UnitError {}
+
+        #[derive(Debug, PartialEq)]
+        #[repr(align(64))]
+        struct AlignedUnitError;
+        impl fmt::Display for AlignedUnitError {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("AlignedUnitError")
+            }
+        }
+        impl std::error::Error for AlignedUnitError {}
+
+        #[allow(unused_mut)]
+        let mut union: ErrorUnion<(UnitError, AlignedUnitError)> = ErrorUnion::new(UnitError);
+        #[cfg(feature = "context")]
+        {
+            union = union.context(String::from("heap context"));
+        }
+        let error = union.into_inner();
+        assert_eq!((&*error as &dyn Any).downcast_ref(), Some(&UnitError));
+
+        #[allow(unused_mut)]
+        let mut union: ErrorUnion<(UnitError, AlignedUnitError)> =
+            ErrorUnion::new(AlignedUnitError);
+        #[cfg(feature = "context")]
+        {
+            union = union.context(String::from("heap context"));
+        }
+        let error = union.into_inner();
+        assert_eq!(
+            &*error as *const dyn SendSyncError as *const u8 as usize % 64,
+            0
+        );
+        assert_eq!(
+            (&*error as &dyn Any).downcast_ref(),
+            Some(&AlignedUnitError)
+        );
+    }
+
+    #[test]
+    fn over_aligned_and_zero_sized_errors_roundtrip_through_dyn_error() {
+        #[derive(Debug, PartialEq)]
+        #[repr(align(64))]
+        struct AlignedError(u8);
+        impl fmt::Display for AlignedError {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "AlignedError({})", self.0)
+            }
+        }
+        impl std::error::Error for AlignedError {}
+
+        #[derive(Debug, PartialEq)]
+        struct UnitError;
+        impl fmt::Display for UnitError {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("UnitError")
+            }
+        }
+        impl std::error::Error for UnitError {}
+
+        let union: ErrorUnion<(AlignedError, UnitError)> = ErrorUnion::new(AlignedError(5));
+        let union: ErrorUnion<(AlignedError, UnitError)> =
+            ErrorUnion::from_dyn_error(union.into_dyn_error()).unwrap();
+        let error = union.into_inner();
+        assert_eq!((&*error as &dyn Any).downcast_ref(), Some(&AlignedError(5)));
+
+        let union: ErrorUnion<(AlignedError, UnitError)> = ErrorUnion::new(UnitError);
+        let union: ErrorUnion<(AlignedError, UnitError)> =
+            ErrorUnion::from_dyn_error(union.into_dyn_error()).unwrap();
+        let unit: UnitError = union.narrow().unwrap();
+        assert_eq!(unit, UnitError);
+    }
+
     #[test]
     fn into_inner_dyn_error_not_roundtrippable_via_from_dyn_error() {
         // Confirm that from_dyn_error correctly rejects a bare inner error
@@ -1511,3 +2260,311 @@ mod downcast_inner_tests {
         assert_eq!(union.downcast_inner_mut::<FooError>(), None);
     }
 }
+
+#[cfg(all(test, feature = "context"))]
+mod frames_tests {
+    use super::*;
+    use std::fmt;
+
+    #[derive(Debug, PartialEq)]
+    struct FooError(String);
+    impl fmt::Display for FooError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "FooError({})", self.0)
+        }
+    }
+    impl std::error::Error for FooError {}
+
+    fn union_with_context() -> ErrorUnion<(FooError,)> {
+        let union: ErrorUnion<(FooError,)> = ErrorUnion::new(FooError("base".into()));
+        union
+            .context("first")
+            .context("second".to_string())
+            .context("third")
+    }
+
+    #[test]
+    fn frames_yields_context_in_order_added() {
+        let union = union_with_context();
+        let frames = union
+            .frames()
+            .map(|frame| frame.context().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(frames, ["first", "second", "third"]);
+    }
+
+    #[test]
+    fn frames_empty_without_context() {
+        let union: ErrorUnion<(FooError,)> = ErrorUnion::new(FooError("base".into()));
+        assert_eq!(union.frames().count(), 0);
+    }
+
+    #[cfg(feature = "location")]
+    #[test]
+    fn frames_expose_location() {
+        let union: ErrorUnion<(FooError,)> = ErrorUnion::new(FooError("base".into()));
+        let line = line!() + 1;
+        let union = union.context("located");
+        let frame = union.frames().next().unwrap();
+        assert_eq!(frame.location().file(), file!());
+        assert_eq!(frame.location().line(), line);
+    }
+
+    #[cfg(feature = "user_context")]
+    #[test]
+    fn frames_expose_user_facing_flag() {
+        let union = union_with_context().user_context("for the user");
+        let flags = union
+            .frames()
+            .map(|frame| frame.is_user_facing())
+            .collect::<Vec<_>>();
+        assert_eq!(flags, [false, false, false, true]);
+    }
+
+    #[test]
+    fn frames_mut_rewrites_context() {
+        let mut union = union_with_context();
+        for frame in union.frames_mut() {
+            *frame.context_mut() = ContextSource::Static("<redacted>");
+        }
+        assert!(
+            union
+                .frames()
+                .all(|frame| frame.context().to_string() == "<redacted>")
+        );
+    }
+
+    #[test]
+    fn frames_hold_typed_attachments() {
+        #[derive(Debug, PartialEq)]
+        struct StatusCode(u16);
+
+        let mut union = union_with_context();
+        {
+            let mut frames = union.frames_mut();
+            frames.next().unwrap().attach(StatusCode(404));
+            let second = frames.next().unwrap();
+            second.attach("request id");
+            second.attach(StatusCode(503));
+        }
+
+        let frame = union.frames().nth(1).unwrap();
+        assert_eq!(
+            frame.attachments::<StatusCode>().collect::<Vec<_>>(),
+            [&StatusCode(503)]
+        );
+        assert_eq!(
+            frame.attachments::<&str>().collect::<Vec<_>>(),
+            [&"request id"]
+        );
+        assert_eq!(frame.attachments::<u16>().count(), 0);
+        assert_eq!(
+            union.attachments::<StatusCode>().collect::<Vec<_>>(),
+            [&StatusCode(404), &StatusCode(503)]
+        );
+        assert!(!format!("{:?}", union).contains("404"));
+    }
+
+    #[test]
+    fn attach_targets_the_context_added_last() {
+        use crate::Context;
+
+        #[derive(Debug, PartialEq)]
+        struct StatusCode(u16);
+
+        let result: Result<(), FooError> = Err(FooError("failed".into()));
+        let union = result
+            .context("first")
+            .attach(StatusCode(404))
+            .context("second")
+            .unwrap_err()
+            .attach(StatusCode(503));
+        let attachments = union
+            .frames()
+            .map(|frame| frame.attachments::<StatusCode>().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(attachments, [[&StatusCode(404)], [&StatusCode(503)]]);
+
+        let union: ErrorUnion<(FooError,)> = ErrorUnion::new(FooError("failed".into()));
+        assert_eq!(
+            union
+                .attach(StatusCode(404))
+                .attachments::<StatusCode>()
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn retain_frames_prunes_context() {
+        let mut union = union_with_context();
+        union.retain_frames(|frame| frame.context().to_string() != "second");
+        let frames = union
+            .frames()
+            .map(|frame| frame.context().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(frames, ["first", "third"]);
+    }
+
+    #[test]
+    fn clear_context_removes_all_frames() {
+        let mut union = union_with_context();
+        union.clear_context();
+        assert_eq!(union.frames().count(), 0);
+        assert!(!format!("{:?}", union).contains("Context:"));
+    }
+
+    #[test]
+    fn first_frames_are_stored_inline() {
+        let mut union = union_with_context();
+        assert_eq!(crate::context::INLINE_FRAMES, 3);
+        assert!(!union.inner.context.spilled());
+
+        union = union.context("fourth");
+        assert!(union.inner.context.spilled());
+        let frames = union
+            .frames()
+            .map(|frame| frame.context().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(frames, ["first", "second", "third", "fourth"]);
+    }
+
+    struct CountingId(u64, &'static std::sync::atomic::AtomicUsize);
+
+    impl fmt::Display for CountingId {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.1.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            write!(f, "while handling request {}", self.0)
+        }
+    }
+
+    #[test]
+    fn context_display_is_formatted_only_when_rendered() {
+        static FORMATTED: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+        let union = union_with_context().context_display(CountingId(7, &FORMATTED));
+        let result: Result<(), FooError> = Err(FooError("base".into()));
+        let handled = crate::Context::context_display(result, CountingId(8, &FORMATTED));
+        drop(handled);
+        assert_eq!(FORMATTED.load(std::sync::atomic::Ordering::Relaxed), 0);
+
+        let frame = union.frames().last().unwrap();
+        assert!(matches!(frame.context(), ContextSource::Display(_)));
+        assert_eq!(frame.context().to_string(), "while handling request 7");
+        assert_eq!(
+            format!("{:?}", frame.context()),
+            "Display(while handling request 7)"
+        );
+        assert_eq!(FORMATTED.load(std::sync::atomic::Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn context_from_format_args() {
+        let id = 7;
+        let union = union_with_context()
+            .context(format_args!("literal"))
+            .context(format_args!("request {id}"));
+        let contexts = union
+            .frames()
+            .map(|frame| frame.context())
+            .collect::<Vec<_>>();
+        assert!(matches!(contexts[3], ContextSource::Static("literal")));
+        assert!(matches!(contexts[4], ContextSource::Owned(s) if s == "request 7"));
+    }
+}
+
+#[cfg(all(test, feature = "return_trace"))]
+mod return_trace_tests {
+    use super::*;
+    use std::fmt;
+
+    #[derive(Debug, PartialEq)]
+    struct FooError;
+    impl fmt::Display for FooError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "FooError")
+        }
+    }
+    impl std::error::Error for FooError {}
+
+    #[derive(Debug, PartialEq)]
+    struct BarError;
+    impl fmt::Display for BarError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "BarError")
+        }
+    }
+    impl std::error::Error for BarError {}
+
+    fn lines(union: &ErrorUnion<impl TypeSet>) -> Vec<u32> {
+        union
+            .return_trace()
+            .iter()
+            .map(|location| {
+                assert_eq!(location.file(), file!());
+                location.line()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn new_error_has_empty_trace() {
+        let union: ErrorUnion<(FooError,)> = ErrorUnion::new(FooError);
+        assert!(union.return_trace().is_empty());
+        assert!(!format!("{:#?}", union).contains("\nReturn Trace:"));
+    }
+
+    #[test]
+    fn records_each_propagation_point_in_order() {
+        fn read() -> Result<(), ErrorUnion<(FooError,)>> {
+            Err(FooError).into_union()
+        }
+        fn load() -> Result<(), ErrorUnion<(FooError, BarError)>> {
+            read().widen()?;
+            Ok(())
+        }
+        fn handle() -> Result<(), ErrorUnion<(FooError, BarError)>> {
+            load().trace()?;
+            Ok(())
+        }
+
+        let union = handle().unwrap_err();
+        let read_line = line!() - 12;
+        assert_eq!(lines(&union), [read_line + 3, read_line + 7]);
+    }
+
+    #[test]
+    fn narrow_and_into_dyn_union_record_the_remainder() {
+        let union: ErrorUnion<(FooError, BarError)> = ErrorUnion::new(BarError);
+        let line = line!() + 1;
+        let remainder = union.narrow::<FooError, _>().unwrap_err();
+        let erased: Result<(), ErrorUnion> = Err(remainder).into_dyn_union();
+        assert_eq!(lines(&erased.unwrap_err()), [line, line + 1]);
+    }
+
+    #[test]
+    fn new_errors_are_not_traced() {
+        let union = Err::<(), _>(FooError).into_union::<_, (FooError,)>();
+        assert!(union.unwrap_err().return_trace().is_empty());
+        let union = Err::<(), _>(FooError).into_dyn_union();
+        assert!(union.unwrap_err().return_trace().is_empty());
+    }
+
+    #[test]
+    fn trace_is_kept_through_map() {
+        let union: ErrorUnion<(FooError,)> = ErrorUnion::new(FooError);
+        let line = line!() + 1;
+        let union = union.trace().map(|_| BarError);
+        assert_eq!(lines(&union), [line]);
+    }
+
+    #[test]
+    fn report_has_return_trace_section() {
+        let union: ErrorUnion<(FooError,)> = ErrorUnion::new(FooError);
+        let line = line!() + 1;
+        let union = union.trace();
+        let report = format!("{:#?}", union);
+        let expected = format!("Return Trace:\n\t0: {}:{}:", file!(), line);
+        assert!(report.contains(&expected), "{report}");
+    }
+}
@@ -0,0 +1,394 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{self, Poll, ready};
+
+use futures_core::Stream;
+
+use crate::{Context, ContextSource, ErrorUnion};
+
+/// Provides `context` methods for futures that resolve to a `Result` or `Option`, so context can
+/// be added without awaiting first, e.g. `fetch(url).context("fetching config").await?`.
+pub trait FutureExt: Future + Sized {
+    /// Adds additional context to the output, when the future completes. This becomes a no-op if
+    /// the `context` feature is disabled.
+    #[cfg_attr(feature = "location", track_caller)]
+    fn context<C: Into<ContextSource>>(self, context: C) -> ContextFuture<Self, C>
+    where
+        Self::Output: Context,
+    {
+        ContextFuture {
+            future: self,
+            context: Some(context),
+            #[cfg(feature = "location")]
+            location: core::panic::Location::caller(),
+        }
+    }
+
+    /// Lazily adds additional context to the output, when the future completes with an error.
+    /// This becomes a no-op if the `context` feature is disabled.
+    #[cfg_attr(feature = "location", track_caller)]
+    fn with_context<F, C>(self, f: F) -> WithContextFuture<Self, F>
+    where
+        Self::Output: Context,
+        F: FnOnce() -> C,
+        C: Into<ContextSource>,
+    {
+        WithContextFuture {
+            future: self,
+            f: Some(f),
+            #[cfg(feature = "location")]
+            location: core::panic::Location::caller(),
+        }
+    }
+}
+
+impl<F: Future> FutureExt for F {}
+
+/// A future with context, created with [`FutureExt::context`]
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct ContextFuture<F, C> {
+    future: F,
+    context: Option<C>,
+    #[cfg(feature = "location")]
+    location: &'static core::panic::Location<'static>,
+}
+
+impl<F, C> Future for ContextFuture<F, C>
+where
+    F: Future,
+    F::Output: Context,
+    C: Into<ContextSource>,
+{
+    type Output = Result<<F::Output as Context>::Okay, ErrorUnion<<F::Output as Context>::OutSet>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: `future` is structurally pinned, it is never moved out of `self`, and `context`
+        // is not
+        let this = unsafe { self.get_unchecked_mut() };
+        let output = ready!(unsafe { Pin::new_unchecked(&mut this.future) }.poll(cx));
+        let context = this
+            .context
+            .take()
+            .expect("`ContextFuture` polled after completion");
+        #[cfg(feature = "location")]
+        let result = output.with_context_at(|| context, this.location);
+        #[cfg(not(feature = "location"))]
+        let result = output.context(context);
+        Poll::Ready(result)
+    }
+}
+
+/// A future with lazy context, created with [`FutureExt::with_context`]
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct WithContextFuture<F, G> {
+    future: F,
+    f: Option<G>,
+    #[cfg(feature = "location")]
+    location: &'static core::panic::Location<'static>,
+}
+
+impl<F, G, C> Future for WithContextFuture<F, G>
+where
+    F: Future,
+    F::Output: Context,
+    G: FnOnce() -> C,
+    C: Into<ContextSource>,
+{
+    type Output = Result<<F::Output as Context>::Okay, ErrorUnion<<F::Output as Context>::OutSet>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: `future` is structurally pinned, it is never moved out of `self`, and `f` is not
+        let this = unsafe { self.get_unchecked_mut() };
+        let output = ready!(unsafe { Pin::new_unchecked(&mut this.future) }.poll(cx));
+        let f = this
+            .f
+            .take()
+            .expect("`WithContextFuture` polled after completion");
+        #[cfg(feature = "location")]
+        let result = output.with_context_at(f, this.location);
+        #[cfg(not(feature = "location"))]
+        let result = output.with_context(f);
+        Poll::Ready(result)
+    }
+}
+
+/// Provides `context` methods for streams of `Result`s, mapping each error into an
+/// [`ErrorUnion`] with context.
+pub trait StreamExt: Stream + Sized {
+    /// Adds additional context to each error. This becomes a no-op if the `context` feature is
+    /// disabled.
+    #[cfg_attr(feature = "location", track_caller)]
+    fn context<C>(self, context: C) -> ContextStream<Self, C>
+    where
+        Self::Item: Context,
+        C: Into<ContextSource> + Clone,
+    {
+        ContextStream {
+            stream: self,
+            context,
+            #[cfg(feature = "location")]
+            location: core::panic::Location::caller(),
+        }
+    }
+
+    /// Lazily adds additional context to each error from the index of the item in the stream,
+    /// e.g. `.with_context(|index| format!("reading row {index}"))`. This becomes a no-op if the
+    /// `context` feature is disabled.
+    #[cfg_attr(feature = "location", track_caller)]
+    fn with_context<F, C>(self, f: F) -> WithContextStream<Self, F>
+    where
+        Self::Item: Context,
+        F: FnMut(usize) -> C,
+        C: Into<ContextSource>,
+    {
+        WithContextStream {
+            stream: self,
+            f,
+            index: 0,
+            #[cfg(feature = "location")]
+            location: core::panic::Location::caller(),
+        }
+    }
+}
+
+impl<S: Stream> StreamExt for S {}
+
+/// A stream with context, created with [`StreamExt::context`]
+#[must_use = "streams do nothing unless polled"]
+#[derive(Debug)]
+pub struct ContextStream<S, C> {
+    stream: S,
+    context: C,
+    #[cfg(feature = "location")]
+    location: &'static core::panic::Location<'static>,
+}
+
+impl<S, C> Stream for ContextStream<S, C>
+where
+    S: Stream,
+    S::Item: Context,
+    C: Into<ContextSource> + Clone,
+{
+    type Item = Result<<S::Item as Context>::Okay, ErrorUnion<<S::Item as Context>::OutSet>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        // SAFETY: `stream` is structurally pinned, it is never moved out of `self`, and `context`
+        // is not
+        let this = unsafe { self.get_unchecked_mut() };
+        let Some(item) = ready!(unsafe { Pin::new_unchecked(&mut this.stream) }.poll_next(cx))
+        else {
+            return Poll::Ready(None);
+        };
+        let context = &this.context;
+        #[cfg(feature = "location")]
+        let result = item.with_context_at(|| context.clone(), this.location);
+        #[cfg(not(feature = "location"))]
+        let result = item.with_context(|| context.clone());
+        Poll::Ready(Some(result))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+/// A stream with lazy, per item context, created with [`StreamExt::with_context`]
+#[must_use = "streams do nothing unless polled"]
+#[derive(Debug)]
+pub struct WithContextStream<S, F> {
+    stream: S,
+    f: F,
+    index: usize,
+    #[cfg(feature = "location")]
+    location: &'static core::panic::Location<'static>,
+}
+
+impl<S, F, C> Stream for WithContextStream<S, F>
+where
+    S: Stream,
+    S::Item: Context,
+    F: FnMut(usize) -> C,
+    C: Into<ContextSource>,
+{
+    type Item = Result<<S::Item as Context>::Okay, ErrorUnion<<S::Item as Context>::OutSet>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        // SAFETY: `stream` is structurally pinned, it is never moved out of `self`, and `f` is not
+        let this = unsafe { self.get_unchecked_mut() };
+        let Some(item) = ready!(unsafe { Pin::new_unchecked(&mut this.stream) }.poll_next(cx))
+        else {
+            return Poll::Ready(None);
+        };
+        let index = this.index;
+        this.index += 1;
+        let f = &mut this.f;
+        #[cfg(feature = "location")]
+        let result = item.with_context_at(|| f(index), this.location);
+        #[cfg(not(feature = "location"))]
+        let result = item.with_context(|| f(index));
+        Poll::Ready(Some(result))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StrError;
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+    use alloc::{format, vec};
+
+    /// Polls `future` to completion, it must not wait on anything
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = core::pin::pin!(future);
+        let mut cx = task::Context::from_waker(task::Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    /// Yields once before resolving to `output`
+    async fn later<T>(output: T) -> T {
+        let mut yielded = false;
+        core::future::poll_fn(|cx| {
+            if yielded {
+                return Poll::Ready(());
+            }
+            yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        })
+        .await;
+        output
+    }
+
+    struct Iter<I>(I);
+
+    impl<I: Iterator + Unpin> Stream for Iter<I> {
+        type Item = I::Item;
+
+        fn poll_next(mut self: Pin<&mut Self>, _: &mut task::Context<'_>) -> Poll<Option<I::Item>> {
+            Poll::Ready(self.0.next())
+        }
+    }
+
+    fn collect<S: Stream>(stream: S) -> Vec<S::Item> {
+        let mut stream = core::pin::pin!(stream);
+        let mut cx = task::Context::from_waker(task::Waker::noop());
+        let mut items = Vec::new();
+        while let Poll::Ready(Some(item)) = stream.as_mut().poll_next(&mut cx) {
+            items.push(item);
+        }
+        items
+    }
+
+    fn contexts<E: crate::TypeSet>(error: &ErrorUnion<E>) -> Vec<String> {
+        error.frames().map(|f| f.context().to_string()).collect()
+    }
+
+    #[test]
+    fn future_context_is_added_on_completion() {
+        let error = block_on(later(Err::<(), _>(StrError::from("offline"))).context("syncing"))
+            .unwrap_err();
+        assert_eq!(contexts(&error), ["syncing"]);
+
+        let value = block_on(later(Ok::<_, StrError>(1)).context("syncing")).unwrap();
+        assert_eq!(value, 1);
+    }
+
+    #[test]
+    fn future_with_context_is_only_called_on_error() {
+        let mut calls = 0;
+        let value = block_on(later(Some(1)).with_context(|| {
+            calls += 1;
+            "missing value"
+        }))
+        .unwrap();
+        assert_eq!(value, 1);
+        assert_eq!(calls, 0);
+
+        let error = block_on(later(None::<u8>).with_context(|| "missing value")).unwrap_err();
+        assert_eq!(contexts(&error), ["missing value"]);
+    }
+
+    #[cfg(feature = "location")]
+    #[test]
+    fn future_context_points_at_the_caller() {
+        let line = line!() + 1;
+        let future = later(Err::<(), _>(StrError::from("offline"))).context("syncing");
+        let error = block_on(future).unwrap_err();
+        let frame = error.frames().next().unwrap();
+        assert_eq!(
+            (frame.location().file(), frame.location().line()),
+            (file!(), line)
+        );
+        assert!(format!("{error:#?}").contains(&format!("{}:{line}:", file!())));
+    }
+
+    #[cfg(feature = "location")]
+    #[test]
+    fn stream_and_option_context_point_at_the_caller() {
+        let rows = Iter(vec![Err::<u8, _>(StrError::from("eof"))].into_iter());
+        let line = line!() + 1;
+        let items = collect(rows.context("reading"));
+        let error = items[0].as_ref().unwrap_err();
+        assert_eq!(
+            (error.location().file(), error.location().line()),
+            (file!(), line)
+        );
+        let frame = error.frames().next().unwrap();
+        assert_eq!(
+            (frame.location().file(), frame.location().line()),
+            (file!(), line)
+        );
+
+        let line = line!() + 1;
+        let error = block_on(later(None::<u8>).with_context(|| "missing value")).unwrap_err();
+        assert_eq!(
+            (error.location().file(), error.location().line()),
+            (file!(), line)
+        );
+    }
+
+    #[cfg(feature = "location")]
+    #[test]
+    fn existing_error_keeps_its_location() {
+        let line = line!() + 1;
+        let error: ErrorUnion<(StrError,)> = ErrorUnion::new(StrError::from("offline"));
+        let error = block_on(later(Err::<(), _>(error)).context("syncing")).unwrap_err();
+        assert_eq!(error.location().line(), line);
+        assert_eq!(error.frames().next().unwrap().location().line(), line + 1);
+    }
+
+    #[test]
+    fn stream_errors_get_per_item_context() {
+        let rows = vec![
+            Ok(1),
+            Err(StrError::from("bad row")),
+            Ok(3),
+            Err(StrError::from("eof")),
+        ];
+        let items = collect(Iter(rows.into_iter()).with_context(|index| format!("row {index}")));
+        assert_eq!(items.len(), 4);
+        assert_eq!(*items[2].as_ref().unwrap(), 3);
+        assert_eq!(contexts(items[1].as_ref().unwrap_err()), ["row 1"]);
+        assert_eq!(contexts(items[3].as_ref().unwrap_err()), ["row 3"]);
+    }
+
+    #[test]
+    fn stream_context_is_added_to_each_error() {
+        let items: Vec<Result<u8, _>> = vec![Err(StrError::from("a")), Err(StrError::from("b"))];
+        let items = collect(Iter(items.into_iter()).context("reading rows"));
+        for item in &items {
+            assert_eq!(contexts(item.as_ref().unwrap_err()), ["reading rows"]);
+        }
+    }
+}
@@ -16,9 +16,31 @@ pub mod __private {
 }
 
 mod any_error;
+#[cfg(feature = "backtrace")]
+mod backtrace;
+mod chain;
 mod context;
+#[cfg(feature = "futures")]
+mod futures;
 mod macros;
+#[cfg(any(feature = "backtrace", feature = "span_trace"))]
+mod policy;
+mod redact;
+#[cfg(any(
+    feature = "logging",
+    feature = "user_context",
+    feature = "backtrace",
+    feature = "span_trace"
+))]
+mod registry;
+mod report;
+#[cfg(feature = "scope")]
+mod scope;
+#[cfg(feature = "span_trace")]
+mod span_trace;
 mod str_error;
+mod sync;
+mod theme;
 mod error_union;
 mod type_set;
 mod union_to_enum;
@@ -28,7 +50,7 @@ mod user_context;
 mod logging;
 
 #[cfg(feature = "logging")]
-pub use logging::LogExt;
+pub use logging::{Level, LogExt, Severity, register_severity};
 
 // re-export macro
 pub use eros_macros::context;
@@ -38,10 +60,39 @@ pub type Result<T, E = AnyError> = core::result::Result<T, ErrorUnion<E>>;
 
 // data structures
 pub use any_error::AnyError;
+#[cfg(feature = "backtrace")]
+pub use backtrace::{
+    BacktraceConfig, BacktracePolicy, BacktraceVerbosity, backtrace_policy,
+    set_backtrace_policy, with_backtrace_policy,
+};
+pub use chain::Chain;
 pub use context::ContextSource;
+pub use context::ContextFrame;
+pub use context::FrameKind;
+pub use context::KeyValue;
 #[cfg(feature = "context")]
 pub use context::AbsentValueError;
+#[cfg(feature = "span_trace")]
+pub use span_trace::{
+    SpanTracePolicy, set_span_trace_policy, span_trace_policy, with_span_trace_policy,
+};
+#[cfg(any(feature = "backtrace", feature = "span_trace"))]
+pub use policy::register_expected;
 pub use str_error::StrError;
+#[cfg(feature = "user_context")]
+pub use user_context::{Translator, UserFacing, UserMessage, register_user_facing};
+pub use redact::{REDACTED, RedactionPolicy, Secret, redaction_policy, set_redaction_policy};
+#[cfg(feature = "std")]
+pub use redact::with_redaction_policy;
+pub use report::{ReportConfig, report_config, set_report_config};
+pub use theme::{Color, Style, Theme};
+#[cfg(feature = "std")]
+pub use report::with_report_config;
+#[cfg(feature = "scope")]
+pub use scope::{
+    ErrorScope, ErrorScopeWith, ScopeContext, ScopeFutureExt, ScopeGuard, ScopeStack, ScopeStorage,
+    push_context, scope, scope_with, set_scope_storage,
+};
 pub use error_union::SendSyncError;
 pub use error_union::ErrorUnion;
 pub use type_set::TypeSet;
@@ -56,6 +107,10 @@ pub use type_set::{
 
 // traits
 pub use context::Context;
+#[cfg(feature = "futures")]
+pub use futures::{
+    ContextFuture, ContextStream, FutureExt, StreamExt, WithContextFuture, WithContextStream,
+};
 pub use error_union::ReshapeUnion;
 pub use error_union::IntoUnion;
 pub use error_union::IntoDynUnion;
\ No newline at end of file
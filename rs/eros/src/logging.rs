@@ -1,13 +1,186 @@
 use core::result::Result;
 
 use crate::{
-    ErrorUnion,
+    ErrorUnion, SendSyncError,
+    registry::Registry,
     type_set::{DebugFold, DisplayFold, TypeSet},
 };
 
+/// The level an error is logged at, mapped onto the level of each enabled logging backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+#[cfg(feature = "tracing")]
+impl From<Level> for tracing::Level {
+    fn from(level: Level) -> Self {
+        match level {
+            Level::Error => tracing::Level::ERROR,
+            Level::Warn => tracing::Level::WARN,
+            Level::Info => tracing::Level::INFO,
+            Level::Debug => tracing::Level::DEBUG,
+            Level::Trace => tracing::Level::TRACE,
+        }
+    }
+}
+
+#[cfg(feature = "log")]
+impl From<Level> for log::Level {
+    fn from(level: Level) -> Self {
+        match level {
+            Level::Error => log::Level::Error,
+            Level::Warn => log::Level::Warn,
+            Level::Info => log::Level::Info,
+            Level::Debug => log::Level::Debug,
+            Level::Trace => log::Level::Trace,
+        }
+    }
+}
+
+/// The level an error should be logged at, used by [`LogExt::log_by_severity`] and
+/// [`ErrorUnion::log_by_severity`]. Defaults to [`Level::Error`], so expected errors only need to
+/// override it. `ErrorUnion` itself implements `Severity` with the severity registered for the
+/// type of its inner error, see [`register_severity`].
+///
+/// ```rust,ignore
+/// impl Severity for NotFoundError {
+///     fn severity(&self) -> Level {
+///         Level::Debug
+///     }
+/// }
+///
+/// register_severity(NotFoundError::severity);
+/// ```
+pub trait Severity {
+    fn severity(&self) -> Level {
+        Level::Error
+    }
+}
+
+impl<T> Severity for crate::Secret<T> {}
+
+macro_rules! impl_error_severity {
+    ($($ty:ty),* $(,)?) => {
+        $(impl Severity for $ty {})*
+    };
+}
+
+impl_error_severity!(
+    crate::StrError,
+    core::fmt::Error,
+    core::num::ParseIntError,
+    core::num::ParseFloatError,
+    core::num::TryFromIntError,
+    core::str::ParseBoolError,
+    core::str::Utf8Error,
+    core::char::ParseCharError,
+    core::array::TryFromSliceError,
+    alloc::string::FromUtf8Error,
+);
+
+#[cfg(feature = "context")]
+impl_error_severity!(crate::AbsentValueError);
+
+#[cfg(feature = "std")]
+impl_error_severity!(std::io::Error);
+
+static SEVERITIES: Registry<Level> = Registry::new();
+
+/// Sets the severity of errors of type `T`, used when an [`ErrorUnion`] holding a `T` is logged by
+/// [`LogExt::log_by_severity`] or [`ErrorUnion::log_by_severity`]. Errors of a type without a
+/// registered severity are logged at [`Level::Error`]. Registering again replaces the previous
+/// severity. Since `severity` is a function, this also works for foreign types, which cannot
+/// implement [`Severity`].
+///
+/// ```rust,ignore
+/// register_severity(NotFoundError::severity);
+/// register_severity(|error: &std::io::Error| match error.kind() {
+///     std::io::ErrorKind::NotFound => Level::Debug,
+///     _ => Level::Error,
+/// });
+/// ```
+pub fn register_severity<T: SendSyncError>(severity: fn(&T) -> Level) {
+    SEVERITIES.register(severity);
+}
+
+impl<E: TypeSet> Severity for ErrorUnion<E> {
+    /// The severity registered for the type of the inner error with [`register_severity`],
+    /// otherwise [`Level::Error`]
+    fn severity(&self) -> Level {
+        SEVERITIES
+            .call(self.inner.error.as_any())
+            .unwrap_or(Level::Error)
+    }
+}
+
+/// Logs errors of `Result`s through the logging backends enabled by feature flag, `tracing` and/or
+/// `log`. Besides the message, each event carries the error as structured fields (key-values for
+/// `log`), so structured subscribers, e.g. JSON, can index them:
+/// - `error.type`: the type name of the error, e.g. `std::io::error::Error`
+/// - `error.message`: the display of the error itself, without context
+/// - `error.context`: the context, innermost first. A sequence for `log`, for `tracing` a debug
+///   string, or a `valuable` list with the `valuable` feature and `--cfg tracing_unstable`
+/// - `error.location`: where the error was created, with the `location` feature
+/// - `error.attachments`: the [`KeyValue`](crate::KeyValue) attachments of the context frames, as
+///   a map from key to the displayed value. A map for `log`, for `tracing` a debug string
+///
+/// The message is the `{:#?}` report with the `log_debug` feature, otherwise the `{}` display.
 pub trait LogExt<O> {
+    /// The error that is logged
+    type Error;
+
+    /// If `Err`, logs this error as "error"
     fn log_error(self) -> O;
+    /// If `Err`, logs this error as "warn"
     fn log_warn(self) -> O;
+    /// If `Err`, logs this error as "info"
+    fn log_info(self) -> O;
+    /// If `Err`, logs this error as "debug"
+    fn log_debug(self) -> O;
+    /// If `Err`, logs this error as "trace"
+    fn log_trace(self) -> O;
+    /// If `Err`, logs this error at `level`
+    fn log_at(self, level: Level) -> O;
+    /// If `Err`, logs this error at the level of its [`Severity`], e.g. an expected "not found"
+    /// error at debug and a database failure at error, see [`register_severity`]
+    fn log_by_severity(self) -> O;
+}
+
+macro_rules! impl_log_ext {
+    () => {
+        fn log_error(self) -> Self {
+            self.log_at(Level::Error)
+        }
+
+        fn log_warn(self) -> Self {
+            self.log_at(Level::Warn)
+        }
+
+        fn log_info(self) -> Self {
+            self.log_at(Level::Info)
+        }
+
+        fn log_debug(self) -> Self {
+            self.log_at(Level::Debug)
+        }
+
+        fn log_trace(self) -> Self {
+            self.log_at(Level::Trace)
+        }
+
+        fn log_at(self, level: Level) -> Self {
+            self.inspect_err(|e| e.log_at(level))
+        }
+
+        fn log_by_severity(self) -> Self {
+            self.inspect_err(|e| e.log_by_severity())
+        }
+    };
 }
 
 impl<T, E> LogExt<Result<T, ErrorUnion<E>>> for Result<T, ErrorUnion<E>>
@@ -15,39 +188,57 @@ where
     E: TypeSet,
     <E as TypeSet>::Variants: core::fmt::Debug + DebugFold + core::fmt::Display + DisplayFold,
 {
-    /// If `Err`, logs this error as "error". The logging backend is configured by feature flag, as well as
-    /// if the error is logged as its display or debug version
-    fn log_error(self) -> Result<T, ErrorUnion<E>> {
-        self.inspect_err(|e| {
-            e.log_error();
-        })
-    }
+    type Error = ErrorUnion<E>;
 
-    /// If `Err`, logs this error as "warn". The logging backend is configured by feature flag, as well as
-    /// if the error is logged as its display or debug version
-    fn log_warn(self) -> Result<T, ErrorUnion<E>> {
-        self.inspect_err(|e| {
-            e.log_warn();
-        })
-    }
+    impl_log_ext!();
 }
 
 impl<T> LogExt<Result<T, ErrorUnion>> for Result<T, ErrorUnion> {
-    /// If `Err`, logs this error as "error". The logging backend is configured by feature flag, as well as
-    /// if the error is logged as its display or debug version
-    fn log_error(self) -> Result<T, ErrorUnion> {
-        self.inspect_err(|e| {
-            e.log_error();
-        })
-    }
+    type Error = ErrorUnion;
 
-    /// If `Err`, logs this error as "warn". The logging backend is configured by feature flag, as well as
-    /// if the error is logged as its display or debug version
-    fn log_warn(self) -> Result<T, ErrorUnion> {
-        self.inspect_err(|e| {
-            e.log_warn();
-        })
-    }
+    impl_log_ext!();
+}
+
+macro_rules! impl_log_methods {
+    () => {
+        /// Logs this error as "error". The logging backend is configured by feature flag, as well
+        /// as if the message is the display or debug version of the error. The event also carries
+        /// the error as structured fields, see [`LogExt`].
+        #[cfg(feature = "logging")]
+        pub fn log_error(&self) {
+            self.log_at(Level::Error);
+        }
+
+        /// Logs this error as "warn", see [`ErrorUnion::log_error`]
+        #[cfg(feature = "logging")]
+        pub fn log_warn(&self) {
+            self.log_at(Level::Warn);
+        }
+
+        /// Logs this error as "info", see [`ErrorUnion::log_error`]
+        #[cfg(feature = "logging")]
+        pub fn log_info(&self) {
+            self.log_at(Level::Info);
+        }
+
+        /// Logs this error as "debug", see [`ErrorUnion::log_error`]
+        #[cfg(feature = "logging")]
+        pub fn log_debug(&self) {
+            self.log_at(Level::Debug);
+        }
+
+        /// Logs this error as "trace", see [`ErrorUnion::log_error`]
+        #[cfg(feature = "logging")]
+        pub fn log_trace(&self) {
+            self.log_at(Level::Trace);
+        }
+
+        /// Logs this error at `level`, see [`ErrorUnion::log_error`]
+        #[cfg(feature = "logging")]
+        pub fn log_at(&self, level: Level) {
+            log_at(self, level);
+        }
+    };
 }
 
 impl<E> ErrorUnion<E>
@@ -55,67 +246,211 @@ where
     E: TypeSet,
     <E as TypeSet>::Variants: core::fmt::Debug + DebugFold + core::fmt::Display + DisplayFold,
 {
-    /// Logs this error as "error". The logging backend is configured by feature flag, as well as
-    /// if the error is logged as its display or debug version
-    #[cfg(feature = "logging")]
-    pub fn log_error(&self) {
-        #[cfg(all(
-            feature = "log_display",
-            not(feature = "log_debug"),
-            feature = "tracing"
-        ))]
-        tracing::error!("{}", self);
-        #[cfg(all(feature = "log_debug", feature = "tracing"))]
-        tracing::error!("{:#?}", self);
-    }
-
-    /// Logs this error as "warn". The logging backend is configured by feature flag, as well as
-    /// if the error is logged as its display or debug version
-    #[cfg(feature = "logging")]
-    pub fn log_warn(&self) {
-        #[cfg(all(
-            feature = "log_display",
-            not(feature = "log_debug"),
-            feature = "tracing"
-        ))]
-        tracing::warn!("{}", self);
-        #[cfg(all(feature = "log_debug", feature = "tracing"))]
-        tracing::warn!("{:#?}", self);
-    }
+    impl_log_methods!();
 }
 
 impl ErrorUnion {
-    /// Logs this error as "error". The logging backend is configured by feature flag, as well as
-    /// if the error is logged as its display or debug version
-    #[cfg(feature = "logging")]
-    pub fn log_error(&self) {
-        #[cfg(all(
-            feature = "log_display",
-            not(feature = "log_debug"),
-            feature = "tracing"
-        ))]
-        tracing::error!("{}", self);
-        #[cfg(all(feature = "log_debug", feature = "tracing"))]
-        tracing::error!("{:#?}", self);
-    }
-
-    /// Logs this error as "warn". The logging backend is configured by feature flag, as well as
-    /// if the error is logged as its display or debug version
+    impl_log_methods!();
+}
+
+impl<E> ErrorUnion<E>
+where
+    E: TypeSet,
+    Self: core::fmt::Display + core::fmt::Debug,
+{
+    /// Logs this error at the level of its [`Severity`], see [`ErrorUnion::log_error`]
     #[cfg(feature = "logging")]
-    pub fn log_warn(&self) {
-        #[cfg(all(
-            feature = "log_display",
-            not(feature = "log_debug"),
-            feature = "tracing"
-        ))]
-        tracing::warn!("{}", self);
-        #[cfg(all(feature = "log_debug", feature = "tracing"))]
-        tracing::warn!("{:#?}", self);
+    pub fn log_by_severity(&self) {
+        log_at(self, self.severity());
+    }
+}
+
+/// Logs `error` at `level` through each enabled logging backend
+fn log_at<E: TypeSet>(error: &ErrorUnion<E>, level: Level)
+where
+    ErrorUnion<E>: core::fmt::Display + core::fmt::Debug,
+{
+    #[cfg(feature = "tracing")]
+    trace_event(error, level.into());
+    #[cfg(feature = "log")]
+    log_record(error, level.into());
+    #[cfg(not(any(feature = "tracing", feature = "log")))]
+    let _ = (error, level);
+}
+
+/// The message of a log event, the `{:#?}` report with `log_debug`, otherwise the `{}` display
+#[cfg(any(feature = "tracing", feature = "log"))]
+struct Message<'a, T>(&'a T);
+
+#[cfg(any(feature = "tracing", feature = "log"))]
+impl<T: core::fmt::Display + core::fmt::Debug> core::fmt::Display for Message<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if cfg!(feature = "log_debug") {
+            write!(f, "{:#?}", self.0)
+        } else {
+            write!(f, "{}", self.0)
+        }
+    }
+}
+
+/// The context of an error, innermost first, recorded as a list
+#[cfg(any(feature = "tracing", feature = "log"))]
+struct ContextList<'a, E: TypeSet>(&'a ErrorUnion<E>);
+
+#[cfg(any(feature = "tracing", feature = "log"))]
+impl<E: TypeSet> core::fmt::Debug for ContextList<'_, E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.strings()).finish()
     }
 }
 
+#[cfg(any(feature = "tracing", feature = "log"))]
+impl<E: TypeSet> ContextList<'_, E> {
+    fn strings(&self) -> alloc::vec::Vec<alloc::string::String> {
+        use alloc::string::ToString;
+
+        self.0
+            .frames()
+            .filter(|frame| !frame.kind().is_annotation())
+            .map(|frame| frame.context().to_string())
+            .collect()
+    }
+}
+
+#[cfg(feature = "log")]
+impl<E: TypeSet> serde::Serialize for ContextList<'_, E> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.strings())
+    }
+}
+
+/// The [`KeyValue`](crate::KeyValue) attachments of an error, innermost first, recorded as a map
+#[cfg(any(feature = "tracing", feature = "log"))]
+struct Attachments<'a, E: TypeSet>(&'a ErrorUnion<E>);
+
+#[cfg(any(feature = "tracing", feature = "log"))]
+impl<E: TypeSet> Attachments<'_, E> {
+    fn entries(&self) -> impl Iterator<Item = (&str, alloc::string::String)> {
+        use alloc::string::ToString;
+
+        self.0
+            .attachments::<crate::KeyValue>()
+            .map(|attachment| (attachment.key(), attachment.value().to_string()))
+    }
+}
+
+#[cfg(any(feature = "tracing", feature = "log"))]
+impl<E: TypeSet> core::fmt::Debug for Attachments<'_, E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.entries()).finish()
+    }
+}
+
+#[cfg(feature = "log")]
+impl<E: TypeSet> serde::Serialize for Attachments<'_, E> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.entries())
+    }
+}
+
+/// The context of an error as a `valuable` list, so subscribers can read each frame
+#[cfg(all(feature = "valuable", tracing_unstable))]
+struct ValuableContext(alloc::vec::Vec<alloc::string::String>);
+
+#[cfg(all(feature = "valuable", tracing_unstable))]
+impl valuable::Valuable for ValuableContext {
+    fn as_value(&self) -> valuable::Value<'_> {
+        valuable::Value::Listable(self)
+    }
+
+    fn visit(&self, visit: &mut dyn valuable::Visit) {
+        for context in &self.0 {
+            visit.visit_value(valuable::Value::String(context));
+        }
+    }
+}
+
+#[cfg(all(feature = "valuable", tracing_unstable))]
+impl valuable::Listable for ValuableContext {
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len(), Some(self.0.len()))
+    }
+}
+
+/// Emits a `tracing` event for `error` at `level`, with the error as structured fields
+#[cfg(feature = "tracing")]
+fn trace_event<E: TypeSet>(error: &ErrorUnion<E>, level: tracing::Level)
+where
+    ErrorUnion<E>: core::fmt::Display + core::fmt::Debug,
+{
+    #[cfg(feature = "location")]
+    let location = Some(tracing::field::display(error.location()));
+    #[cfg(not(feature = "location"))]
+    let location: Option<tracing::field::DisplayValue<&str>> = None;
+    #[cfg(all(feature = "valuable", tracing_unstable))]
+    let context = ValuableContext(ContextList(error).strings());
+    #[cfg(all(feature = "valuable", tracing_unstable))]
+    let context = tracing::field::valuable(&context);
+    #[cfg(not(all(feature = "valuable", tracing_unstable)))]
+    let context = tracing::field::debug(ContextList(error));
+    macro_rules! event {
+        ($level:expr) => {
+            tracing::event!(
+                $level,
+                "error.type" = error.type_name(),
+                "error.message" = %error.inner_ref(),
+                "error.context" = context,
+                "error.location" = location,
+                "error.attachments" = ?Attachments(error),
+                "{}",
+                Message(error)
+            )
+        };
+    }
+    if level == tracing::Level::ERROR {
+        event!(tracing::Level::ERROR);
+    } else if level == tracing::Level::WARN {
+        event!(tracing::Level::WARN);
+    } else if level == tracing::Level::INFO {
+        event!(tracing::Level::INFO);
+    } else if level == tracing::Level::DEBUG {
+        event!(tracing::Level::DEBUG);
+    } else {
+        event!(tracing::Level::TRACE);
+    }
+}
+
+/// Logs a record for `error` at `level` through the `log` facade, with the error as key-values
+#[cfg(feature = "log")]
+fn log_record<E: TypeSet>(error: &ErrorUnion<E>, level: log::Level)
+where
+    ErrorUnion<E>: core::fmt::Display + core::fmt::Debug,
+{
+    #[cfg(feature = "location")]
+    log::log!(
+        level,
+        "error.type" = error.type_name(),
+        "error.message":% = error.inner_ref(),
+        "error.context":serde = ContextList(error),
+        "error.location":% = error.location(),
+        "error.attachments":serde = Attachments(error);
+        "{}",
+        Message(error)
+    );
+    #[cfg(not(feature = "location"))]
+    log::log!(
+        level,
+        "error.type" = error.type_name(),
+        "error.message":% = error.inner_ref(),
+        "error.context":serde = ContextList(error),
+        "error.attachments":serde = Attachments(error);
+        "{}",
+        Message(error)
+    );
+}
+
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use std::f32::consts::E;
 
     use super::*;
@@ -149,4 +484,369 @@ mod tests {
         let result = Err::<(), _>(error_union);
         result.log_error();
     }
+
+    #[derive(Debug)]
+    struct NotFound;
+
+    impl core::fmt::Display for NotFound {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            write!(f, "not found")
+        }
+    }
+
+    impl core::error::Error for NotFound {}
+
+    impl Severity for NotFound {
+        fn severity(&self) -> Level {
+            Level::Debug
+        }
+    }
+
+    #[derive(Debug)]
+    struct DatabaseError;
+
+    impl core::fmt::Display for DatabaseError {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            write!(f, "database unavailable")
+        }
+    }
+
+    impl core::error::Error for DatabaseError {}
+
+    impl Severity for DatabaseError {}
+
+    #[test]
+    fn severity_is_registered_by_type() {
+        register_severity(NotFound::severity);
+        let not_found: ErrorUnion<(NotFound, DatabaseError)> = ErrorUnion::new(NotFound);
+        assert_eq!(not_found.severity(), Level::Debug);
+        let database: ErrorUnion<(NotFound, DatabaseError)> = ErrorUnion::new(DatabaseError);
+        assert_eq!(database.severity(), Level::Error);
+
+        let any: ErrorUnion = ErrorUnion::new(NotFound);
+        assert_eq!(any.severity(), Level::Debug);
+    }
+
+    #[test]
+    fn errors_without_a_severity_are_logged_at_error() {
+        let error: ErrorUnion<(core::fmt::Error, DatabaseError)> =
+            ErrorUnion::new(core::fmt::Error);
+        assert_eq!(error.severity(), Level::Error);
+        let _: Result<(), _> = Err::<(), _>(error).log_by_severity();
+    }
+
+    #[cfg(feature = "tracing")]
+    pub(crate) mod tracing_fields {
+        use super::*;
+        use crate::Context;
+        use std::collections::BTreeMap;
+        use std::string::{String, ToString};
+        use std::sync::{Arc, Mutex};
+        use std::vec::Vec;
+        use tracing::field::{Field, Visit};
+        use tracing_subscriber::layer::{Layer, SubscriberExt};
+
+        type Event = BTreeMap<&'static str, String>;
+
+        struct Fields(Event);
+
+        /// Records the fields of every event
+        #[derive(Clone, Default)]
+        struct Recorder(Arc<Mutex<Vec<(tracing::Level, Event)>>>);
+
+        impl Visit for Fields {
+            fn record_debug(&mut self, field: &Field, value: &dyn core::fmt::Debug) {
+                self.0.insert(field.name(), std::format!("{value:?}"));
+            }
+
+            fn record_str(&mut self, field: &Field, value: &str) {
+                self.0.insert(field.name(), value.to_string());
+            }
+
+            /// Lists are recorded as `list` followed by their items
+            #[cfg(all(feature = "valuable", tracing_unstable))]
+            fn record_value(&mut self, field: &Field, value: valuable::Value<'_>) {
+                struct Items(Vec<String>);
+
+                impl valuable::Visit for Items {
+                    fn visit_value(&mut self, value: valuable::Value<'_>) {
+                        if let valuable::Value::String(item) = value {
+                            self.0.push(item.to_string());
+                        }
+                    }
+                }
+
+                let mut items = Items(Vec::new());
+                if let valuable::Value::Listable(list) = value {
+                    list.visit(&mut items);
+                }
+                self.0
+                    .insert(field.name(), std::format!("list{:?}", items.0));
+            }
+        }
+
+        impl<S: tracing::Subscriber> Layer<S> for Recorder {
+            fn on_event(
+                &self,
+                event: &tracing::Event<'_>,
+                _: tracing_subscriber::layer::Context<'_, S>,
+            ) {
+                let mut fields = Fields(Event::new());
+                event.record(&mut fields);
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push((*event.metadata().level(), fields.0));
+            }
+        }
+
+        pub(crate) fn record(f: impl FnOnce()) -> Vec<(tracing::Level, Event)> {
+            let recorder = Recorder::default();
+            let subscriber = tracing_subscriber::registry().with(recorder.clone());
+            tracing::subscriber::with_default(subscriber, f);
+            recorder.0.lock().unwrap().clone()
+        }
+
+        #[test]
+        fn events_carry_the_error_as_fields() {
+            let events = record(|| {
+                let _ = Err::<(), _>(std::io::Error::other("disk full"))
+                    .context("writing cache")
+                    .context("saving session")
+                    .log_warn();
+            });
+            let [(level, fields)] = &events[..] else {
+                panic!("expected one event, got {events:?}");
+            };
+            assert_eq!(*level, tracing::Level::WARN);
+            assert_eq!(fields["error.type"], "std::io::error::Error");
+            assert_eq!(fields["error.message"], "disk full");
+            #[cfg(not(all(feature = "valuable", tracing_unstable)))]
+            assert_eq!(
+                fields["error.context"],
+                r#"["writing cache", "saving session"]"#
+            );
+            #[cfg(all(feature = "valuable", tracing_unstable))]
+            assert_eq!(
+                fields["error.context"],
+                r#"list["writing cache", "saving session"]"#
+            );
+            #[cfg(feature = "location")]
+            assert!(fields["error.location"].starts_with(file!()), "{fields:?}");
+            #[cfg(not(feature = "log_debug"))]
+            assert_eq!(fields["message"], "disk full");
+        }
+
+        #[test]
+        fn any_error_events_carry_the_error_as_fields() {
+            let events = record(|| {
+                let error: ErrorUnion = ErrorUnion::new(crate::StrError::from("request failed"));
+                error.log_error();
+            });
+            let [(level, fields)] = &events[..] else {
+                panic!("expected one event, got {events:?}");
+            };
+            assert_eq!(*level, tracing::Level::ERROR);
+            assert_eq!(fields["error.type"], "eros::str_error::StrError");
+            #[cfg(not(all(feature = "valuable", tracing_unstable)))]
+            assert_eq!(fields["error.context"], "[]");
+            #[cfg(all(feature = "valuable", tracing_unstable))]
+            assert_eq!(fields["error.context"], "list[]");
+        }
+
+        #[test]
+        fn events_carry_key_value_attachments() {
+            let events = record(|| {
+                let error: ErrorUnion<(NotFound,)> = ErrorUnion::new(NotFound);
+                let mut error = error.context("loading user");
+                let frame = error.frames_mut().next().unwrap();
+                frame.attach(crate::KeyValue::new("user_id", 42));
+                frame.attach(404u16);
+                error.log_warn();
+            });
+            let [(_, fields)] = &events[..] else {
+                panic!("expected one event, got {events:?}");
+            };
+            assert_eq!(fields["error.attachments"], r#"{"user_id": "42"}"#);
+        }
+
+        #[test]
+        fn events_are_emitted_at_the_given_level() {
+            let events = record(|| {
+                let _ = Err::<(), _>(NotFound).context("loading user").log_info();
+                let error: ErrorUnion<(NotFound, DatabaseError)> = ErrorUnion::new(DatabaseError);
+                error.log_at(Level::Trace);
+                error.log_by_severity();
+            });
+            let levels: Vec<_> = events.iter().map(|(level, _)| *level).collect();
+            assert_eq!(
+                levels,
+                [
+                    tracing::Level::INFO,
+                    tracing::Level::TRACE,
+                    tracing::Level::ERROR
+                ]
+            );
+        }
+    }
+
+    #[cfg(feature = "log")]
+    pub(crate) mod log_fields {
+        use super::*;
+        use crate::Context;
+        use std::cell::RefCell;
+        use std::collections::BTreeMap;
+        use std::string::{String, ToString};
+        use std::vec::Vec;
+
+        type Record = BTreeMap<String, serde_json::Value>;
+
+        struct Fields(Record);
+
+        /// Records the key-values of every record logged on the current thread
+        struct Recorder;
+
+        std::thread_local! {
+            static RECORDS: RefCell<Vec<(log::Level, Record)>> = const { RefCell::new(Vec::new()) };
+        }
+
+        impl<'kvs> log::kv::VisitSource<'kvs> for Fields {
+            fn visit_pair(
+                &mut self,
+                key: log::kv::Key<'kvs>,
+                value: log::kv::Value<'kvs>,
+            ) -> Result<(), log::kv::Error> {
+                let value = serde_json::to_value(value).unwrap();
+                self.0.insert(key.to_string(), value);
+                Ok(())
+            }
+        }
+
+        impl log::Log for Recorder {
+            fn enabled(&self, _: &log::Metadata<'_>) -> bool {
+                true
+            }
+
+            fn log(&self, record: &log::Record<'_>) {
+                let mut fields = Fields(Record::new());
+                record.key_values().visit(&mut fields).unwrap();
+                fields
+                    .0
+                    .insert("message".to_string(), record.args().to_string().into());
+                RECORDS.with(|records| records.borrow_mut().push((record.level(), fields.0)));
+            }
+
+            fn flush(&self) {}
+        }
+
+        pub(crate) fn record(f: impl FnOnce()) -> Vec<(log::Level, Record)> {
+            static RECORDER: Recorder = Recorder;
+            let _ = log::set_logger(&RECORDER);
+            log::set_max_level(log::LevelFilter::Trace);
+            RECORDS.with(|records| records.borrow_mut().clear());
+            f();
+            RECORDS.with(|records| records.take())
+        }
+
+        #[test]
+        fn records_carry_the_error_as_key_values() {
+            let records = record(|| {
+                let _ = Err::<(), _>(std::io::Error::other("disk full"))
+                    .context("writing cache")
+                    .context("saving session")
+                    .log_error();
+            });
+            let [(level, fields)] = &records[..] else {
+                panic!("expected one record, got {records:?}");
+            };
+            assert_eq!(*level, log::Level::Error);
+            assert_eq!(fields["error.type"], "std::io::error::Error");
+            assert_eq!(fields["error.message"], "disk full");
+            assert_eq!(
+                fields["error.context"],
+                serde_json::json!(["writing cache", "saving session"])
+            );
+            #[cfg(feature = "location")]
+            assert!(
+                fields["error.location"]
+                    .as_str()
+                    .unwrap()
+                    .starts_with(file!()),
+                "{fields:?}"
+            );
+            #[cfg(not(feature = "log_debug"))]
+            assert_eq!(fields["message"], "disk full");
+            #[cfg(feature = "log_debug")]
+            assert!(
+                fields["message"]
+                    .as_str()
+                    .unwrap()
+                    .contains("saving session"),
+                "{fields:?}"
+            );
+        }
+
+        #[test]
+        fn any_error_records_are_logged_at_warn() {
+            let records = record(|| {
+                let error: ErrorUnion = ErrorUnion::new(crate::StrError::from("request failed"));
+                error.log_warn();
+            });
+            let [(level, fields)] = &records[..] else {
+                panic!("expected one record, got {records:?}");
+            };
+            assert_eq!(*level, log::Level::Warn);
+            assert_eq!(fields["error.type"], "eros::str_error::StrError");
+            assert_eq!(fields["error.context"], serde_json::json!([]));
+        }
+
+        #[test]
+        fn records_carry_key_value_attachments() {
+            let records = record(|| {
+                let error: ErrorUnion<(NotFound,)> = ErrorUnion::new(NotFound);
+                let mut error = error.context("loading user");
+                let frame = error.frames_mut().next().unwrap();
+                frame.attach(crate::KeyValue::new("user_id", 42));
+                frame.attach(404u16);
+                error.log_warn();
+            });
+            let [(_, fields)] = &records[..] else {
+                panic!("expected one record, got {records:?}");
+            };
+            assert_eq!(
+                fields["error.attachments"],
+                serde_json::json!({ "user_id": "42" })
+            );
+        }
+
+        #[test]
+        fn records_are_logged_by_severity() {
+            register_severity(NotFound::severity);
+            let records = record(|| {
+                let results: [Result<(), ErrorUnion<(NotFound, DatabaseError)>>; 3] = [
+                    Err(ErrorUnion::new(NotFound)),
+                    Err(ErrorUnion::new(DatabaseError)),
+                    Ok(()),
+                ];
+                for result in results {
+                    let _ = result.log_by_severity();
+                }
+                let _ = Err::<(), _>(NotFound).context("loading user").log_trace();
+                let error: ErrorUnion = ErrorUnion::new(NotFound);
+                error.log_at(Level::Info);
+                error.log_by_severity();
+            });
+            let levels: Vec<_> = records.iter().map(|(level, _)| *level).collect();
+            assert_eq!(
+                levels,
+                [
+                    log::Level::Debug,
+                    log::Level::Error,
+                    log::Level::Trace,
+                    log::Level::Info,
+                    log::Level::Debug
+                ]
+            );
+        }
+    }
 }
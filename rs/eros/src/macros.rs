@@ -44,4 +44,20 @@ macro_rules! ensure {
             $crate::bail!($fmt, $($arg)*);
         }
     };
-}
\ No newline at end of file
+}
+
+/// Creates a localizable [`crate::UserMessage`] from a message id, optional default text and named arguments.
+/// e.g. `msg!("disk-full", path = p)` or `msg!("disk-full", "The disk is full at {path}", path = p)`
+#[cfg(feature = "user_context")]
+#[macro_export]
+macro_rules! msg {
+    ($id:expr, $default_text:literal $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::UserMessage::new($id)
+            .with_default_text($default_text)
+            $(.with_arg(stringify!($name), $value))*
+    };
+    ($id:expr $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::UserMessage::new($id)
+            $(.with_arg(stringify!($name), $value))*
+    };
+}
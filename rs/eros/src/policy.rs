@@ -0,0 +1,93 @@
+use core::any::Any;
+use core::cell::Cell;
+use core::sync::atomic::{AtomicU16, AtomicUsize, Ordering};
+use std::thread::LocalKey;
+
+use crate::SendSyncError;
+use crate::registry::Registry;
+
+const UNSET: u16 = 0;
+
+/// A capture policy, e.g. [`BacktracePolicy`](crate::BacktracePolicy), that can be stored in a
+/// [`PolicyCell`]. Every kind of policy has a distinct `kind`, and the percentage of a sampling
+/// policy is its `percent`.
+pub(crate) trait CapturePolicy: Copy + Default {
+    fn to_parts(self) -> (u8, u8);
+
+    fn from_parts(kind: u8, percent: u8) -> Option<Self>;
+}
+
+/// The global policy, the thread local override of [`PolicyCell::with`] and the counter for
+/// sampling. The policy is stored as a `u16`, the kind in the high byte and the percentage in the
+/// low byte, and `0` when unset, which is also the initial value `local` must have.
+pub(crate) struct PolicyCell {
+    global: AtomicU16,
+    local: &'static LocalKey<Cell<u16>>,
+    sampled: AtomicUsize,
+}
+
+impl PolicyCell {
+    pub(crate) const fn new(local: &'static LocalKey<Cell<u16>>) -> Self {
+        PolicyCell {
+            global: AtomicU16::new(UNSET),
+            local,
+            sampled: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn set<P: CapturePolicy>(&self, policy: P) {
+        self.global.store(encode(policy), Ordering::Relaxed);
+    }
+
+    /// The override of this thread if inside [`PolicyCell::with`], otherwise the global policy
+    pub(crate) fn get<P: CapturePolicy>(&self) -> P {
+        if let Some(policy) = decode(self.local.with(Cell::get)) {
+            return policy;
+        }
+        decode(self.global.load(Ordering::Relaxed)).unwrap_or_default()
+    }
+
+    pub(crate) fn with<P: CapturePolicy, R>(&self, policy: P, f: impl FnOnce() -> R) -> R {
+        struct Reset(&'static LocalKey<Cell<u16>>, u16);
+        impl Drop for Reset {
+            fn drop(&mut self) {
+                self.0.with(|p| p.set(self.1));
+            }
+        }
+        let _reset = Reset(self.local, self.local.with(|p| p.replace(encode(policy))));
+        f()
+    }
+
+    /// If the next error, counted across all threads, is among the first `percent` of its 100
+    pub(crate) fn sample(&self, percent: u8) -> bool {
+        self.sampled.fetch_add(1, Ordering::Relaxed) % 100 < percent as usize
+    }
+}
+
+static EXPECTED: Registry<()> = Registry::new();
+
+/// Marks errors of type `T` as expected, e.g. a `NotFound` that is narrowed and handled right
+/// away. No backtrace or span trace is captured on creating an [`ErrorUnion`](crate::ErrorUnion)
+/// from an expected error, however it is created, unless the policy forces it, e.g.
+/// [`BacktracePolicy::Force`](crate::BacktracePolicy::Force). Single errors of other types can be
+/// created as expected with [`ErrorUnion::expected`](crate::ErrorUnion::expected).
+pub fn register_expected<T: SendSyncError>() {
+    EXPECTED.register(|_: &T| ());
+}
+
+/// If the type of `error` is registered with [`register_expected`]
+pub(crate) fn is_expected(error: &dyn Any) -> bool {
+    EXPECTED.call(error).is_some()
+}
+
+fn encode<P: CapturePolicy>(policy: P) -> u16 {
+    let (kind, percent) = policy.to_parts();
+    (kind as u16 + 1) << 8 | percent.min(100) as u16
+}
+
+fn decode<P: CapturePolicy>(value: u16) -> Option<P> {
+    match value {
+        UNSET => None,
+        _ => P::from_parts((value >> 8) as u8 - 1, value as u8),
+    }
+}
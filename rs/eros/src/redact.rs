@@ -0,0 +1,393 @@
+use core::fmt;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use crate::ContextSource;
+
+/// What sensitive context and [`Secret`] errors are rendered as when redacted
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Controls whether sensitive data is rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedactionPolicy {
+    /// Sensitive data is replaced with [`REDACTED`]
+    Redact,
+    /// Sensitive data is rendered as is
+    Reveal,
+}
+
+impl Default for RedactionPolicy {
+    /// Redacts in release builds and reveals in debug builds
+    fn default() -> Self {
+        if cfg!(debug_assertions) {
+            RedactionPolicy::Reveal
+        } else {
+            RedactionPolicy::Redact
+        }
+    }
+}
+
+const UNSET: u8 = 0;
+const REDACT: u8 = 1;
+const REVEAL: u8 = 2;
+
+static POLICY: AtomicU8 = AtomicU8::new(UNSET);
+
+impl RedactionPolicy {
+    fn to_u8(self) -> u8 {
+        match self {
+            RedactionPolicy::Redact => REDACT,
+            RedactionPolicy::Reveal => REVEAL,
+        }
+    }
+
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            REDACT => Some(RedactionPolicy::Redact),
+            REVEAL => Some(RedactionPolicy::Reveal),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    static POLICY_OVERRIDE: core::cell::Cell<u8> = const { core::cell::Cell::new(UNSET) };
+}
+
+/// Sets the global redaction policy used by every rendering path, e.g. `Display`, `Debug`,
+/// logging and the `anyhow` conversion.
+pub fn set_redaction_policy(policy: RedactionPolicy) {
+    POLICY.store(policy.to_u8(), Ordering::Relaxed);
+}
+
+/// The redaction policy currently in effect on this thread. This is the policy set by
+/// [`with_redaction_policy`] if inside one, otherwise the global policy, otherwise
+/// [`RedactionPolicy::default`].
+pub fn redaction_policy() -> RedactionPolicy {
+    #[cfg(feature = "std")]
+    if let Some(policy) = RedactionPolicy::from_u8(POLICY_OVERRIDE.with(|p| p.get())) {
+        return policy;
+    }
+    RedactionPolicy::from_u8(POLICY.load(Ordering::Relaxed)).unwrap_or_default()
+}
+
+/// Runs `f` with `policy` in effect on the current thread, overriding the global policy.
+/// Useful for a single render, e.g. revealing everything for a local log file while
+/// redacting what is shipped elsewhere.
+#[cfg(feature = "std")]
+pub fn with_redaction_policy<R>(policy: RedactionPolicy, f: impl FnOnce() -> R) -> R {
+    struct Reset(u8);
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            POLICY_OVERRIDE.with(|p| p.set(self.0));
+        }
+    }
+    let _reset = Reset(POLICY_OVERRIDE.with(|p| p.replace(policy.to_u8())));
+    f()
+}
+
+pub(crate) fn should_redact() -> bool {
+    redaction_policy() == RedactionPolicy::Redact
+}
+
+/// Marks data as sensitive, e.g. emails, tokens or file paths. When redacting, it is rendered
+/// as [`REDACTED`].
+///
+/// `Secret` can be used as context, e.g. `.context(Secret(email))`, or as an error itself.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub struct Secret<T>(pub T);
+
+impl<T> Secret<T> {
+    /// Returns the wrapped value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+
+    /// Access to the wrapped value, regardless of the redaction policy
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if should_redact() {
+            f.write_str(REDACTED)
+        } else {
+            fmt::Display::fmt(&self.0, f)
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if should_redact() {
+            f.write_str(REDACTED)
+        } else {
+            fmt::Debug::fmt(&self.0, f)
+        }
+    }
+}
+
+impl<E: core::error::Error> core::error::Error for Secret<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        if should_redact() {
+            None
+        } else {
+            self.0.source()
+        }
+    }
+}
+
+impl<T: Into<ContextSource>> From<Secret<T>> for ContextSource {
+    fn from(secret: Secret<T>) -> Self {
+        ContextSource::Sensitive(alloc::boxed::Box::new(secret.0.into()))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::ErrorUnion;
+
+    #[derive(Debug)]
+    struct LoginError(String);
+
+    impl fmt::Display for LoginError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "login failed for {}", self.0)
+        }
+    }
+
+    impl core::error::Error for LoginError {}
+
+    fn redacted<R>(f: impl FnOnce() -> R) -> R {
+        with_redaction_policy(RedactionPolicy::Redact, f)
+    }
+
+    fn revealed<R>(f: impl FnOnce() -> R) -> R {
+        with_redaction_policy(RedactionPolicy::Reveal, f)
+    }
+
+    #[test]
+    fn default_policy_depends_on_build() {
+        assert_eq!(
+            RedactionPolicy::default() == RedactionPolicy::Redact,
+            !cfg!(debug_assertions)
+        );
+    }
+
+    #[test]
+    fn override_is_restored() {
+        let outer = redaction_policy();
+        redacted(|| {
+            assert_eq!(redaction_policy(), RedactionPolicy::Redact);
+            revealed(|| assert_eq!(redaction_policy(), RedactionPolicy::Reveal));
+            assert_eq!(redaction_policy(), RedactionPolicy::Redact);
+        });
+        assert_eq!(redaction_policy(), outer);
+    }
+
+    /// Sets the global policy for the lifetime of the guard, restoring the previous one on drop,
+    /// even if the test panics. Tests that set the global policy are serialized by the lock, and
+    /// every other test renders with an override, so they are not affected.
+    struct GlobalPolicy {
+        previous: u8,
+        _lock: std::sync::MutexGuard<'static, ()>,
+    }
+
+    impl GlobalPolicy {
+        fn set(policy: RedactionPolicy) -> Self {
+            static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+            let lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            let previous = POLICY.swap(policy.to_u8(), Ordering::Relaxed);
+            GlobalPolicy {
+                previous,
+                _lock: lock,
+            }
+        }
+    }
+
+    impl Drop for GlobalPolicy {
+        fn drop(&mut self) {
+            POLICY.store(self.previous, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn global_policy_applies_without_override() {
+        {
+            let _global = GlobalPolicy::set(RedactionPolicy::Redact);
+            assert_eq!(Secret("token").to_string(), REDACTED);
+            assert_eq!(revealed(|| Secret("token").to_string()), "token");
+        }
+        {
+            let _global = GlobalPolicy::set(RedactionPolicy::Reveal);
+            assert_eq!(Secret("token").to_string(), "token");
+        }
+    }
+
+    #[test]
+    fn secret_error_display_and_debug() {
+        let union: ErrorUnion<(Secret<LoginError>,)> =
+            ErrorUnion::new(Secret(LoginError("bob@example.com".into())));
+
+        let (display, debug) = redacted(|| (union.to_string(), format!("{:?}", union)));
+        assert!(!display.contains("bob@example.com"));
+        assert!(!debug.contains("bob@example.com"));
+        assert!(display.contains(REDACTED));
+
+        let (display, debug) = revealed(|| (union.to_string(), format!("{:?}", union)));
+        assert_eq!(display, "login failed for bob@example.com");
+        assert!(debug.contains("bob@example.com"));
+    }
+
+    #[test]
+    fn secret_error_in_any_error_union() {
+        let union: ErrorUnion = ErrorUnion::new(Secret(LoginError("bob@example.com".into())));
+
+        let (display, debug) = redacted(|| (union.to_string(), format!("{:?}", union)));
+        assert_eq!(display, REDACTED);
+        assert!(!debug.contains("bob@example.com"));
+    }
+
+    #[cfg(feature = "context")]
+    mod context {
+        use super::*;
+        use crate::Context;
+
+        fn union_with_sensitive_context() -> ErrorUnion<(LoginError,)> {
+            let union: ErrorUnion<(LoginError,)> = ErrorUnion::new(LoginError("a user".into()));
+            union
+                .context("loading profile")
+                .sensitive_context(String::from("email was bob@example.com"))
+                .context(Secret("token abc123"))
+        }
+
+        #[test]
+        fn debug_redacts_sensitive_context() {
+            let union = union_with_sensitive_context();
+
+            let output = redacted(|| format!("{:?}", union));
+            assert!(output.contains("loading profile"));
+            assert!(!output.contains("bob@example.com"));
+            assert!(!output.contains("abc123"));
+            assert_eq!(output.matches(REDACTED).count(), 2);
+
+            let output = redacted(|| format!("{:#?}", union));
+            assert!(output.contains("loading profile"));
+            assert!(!output.contains("bob@example.com"));
+            assert!(!output.contains("abc123"));
+
+            let output = revealed(|| format!("{:?}", union));
+            assert!(output.contains("email was bob@example.com"));
+            assert!(output.contains("token abc123"));
+        }
+
+        #[test]
+        fn frame_debug_redacts_sensitive_context() {
+            let union = union_with_sensitive_context();
+            let frames = union.frames().collect::<Vec<_>>();
+
+            let output = redacted(|| format!("{:?}", frames));
+            assert!(!output.contains("bob@example.com"));
+            assert!(!output.contains("abc123"));
+
+            let output = revealed(|| format!("{:?}", frames[1].context()));
+            assert!(output.contains("bob@example.com"));
+        }
+
+        #[test]
+        fn sensitive_context_on_result() {
+            let result: Result<(), LoginError> = Err(LoginError("a user".into()));
+            let union = result
+                .with_sensitive_context(|| format!("path was {}", "/home/bob"))
+                .unwrap_err();
+
+            let output = redacted(|| format!("{:?}", union));
+            assert!(!output.contains("/home/bob"));
+            assert!(output.contains(REDACTED));
+        }
+
+        #[test]
+        fn sensitive_error_context_is_redacted() {
+            let union = union_with_sensitive_context()
+                .context(Secret(Box::new(LoginError("carol@example.com".into()))
+                    as Box<dyn crate::SendSyncError>));
+
+            let output = redacted(|| format!("{:?}", union));
+            assert!(!output.contains("carol@example.com"));
+        }
+
+        #[cfg(feature = "user_context")]
+        #[test]
+        fn sensitive_user_context_is_redacted() {
+            let union = union_with_sensitive_context().user_context(Secret("for bob@example.com"));
+            let user_contexts = redacted(|| {
+                union
+                    .user_contexts()
+                    .map(|c| c.to_string())
+                    .collect::<Vec<_>>()
+            });
+            assert_eq!(user_contexts, vec![REDACTED.to_string()]);
+        }
+
+        #[cfg(feature = "logging")]
+        fn secret_union() -> ErrorUnion<(Secret<LoginError>,)> {
+            let union: ErrorUnion<(Secret<LoginError>,)> =
+                ErrorUnion::new(Secret(LoginError("bob@example.com".into())));
+            union
+                .context("loading profile")
+                .sensitive_context(String::from("token abc123"))
+        }
+
+        #[cfg(all(feature = "logging", feature = "tracing"))]
+        #[test]
+        fn trace_event_fields_are_redacted() {
+            let union = secret_union();
+            let events =
+                redacted(|| crate::logging::tests::tracing_fields::record(|| union.log_error()));
+            let [(_, fields)] = &events[..] else {
+                panic!("expected one event, got {events:?}");
+            };
+            assert_eq!(fields["error.message"], REDACTED);
+            assert!(fields["error.context"].contains("loading profile"));
+            assert!(fields["error.context"].contains(REDACTED));
+            assert!(!fields["error.context"].contains("abc123"));
+            assert!(!format!("{fields:?}").contains("bob@example.com"));
+        }
+
+        #[cfg(all(feature = "logging", feature = "log"))]
+        #[test]
+        fn log_record_fields_are_redacted() {
+            let union = secret_union();
+            let records =
+                redacted(|| crate::logging::tests::log_fields::record(|| union.log_error()));
+            let [(_, fields)] = &records[..] else {
+                panic!("expected one record, got {records:?}");
+            };
+            assert_eq!(fields["error.message"], REDACTED);
+            assert_eq!(
+                fields["error.context"],
+                serde_json::json!(["loading profile", REDACTED])
+            );
+            assert!(!format!("{fields:?}").contains("bob@example.com"));
+            assert!(!format!("{fields:?}").contains("abc123"));
+        }
+
+        #[cfg(feature = "anyhow")]
+        #[test]
+        fn anyhow_conversion_redacts_sensitive_context() {
+            let union = ErrorUnion::erase(union_with_sensitive_context());
+            let error: anyhow::Error = union.into();
+
+            let output = redacted(|| format!("{:?}", error));
+            assert!(output.contains("loading profile"));
+            assert!(!output.contains("bob@example.com"));
+            assert!(!output.contains("abc123"));
+
+            let output = revealed(|| format!("{:?}", error));
+            assert!(output.contains("bob@example.com"));
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use alloc::{sync::Arc, vec::Vec};
+use core::any::{Any, TypeId};
+
+use crate::sync::Lock;
+
+type Function<R> = Arc<dyn Fn(&dyn Any) -> R + Send + Sync>;
+
+/// Functions registered by error type. Used for per-type behavior, e.g. the severity of an error,
+/// that cannot be a trait bound on every variant of a union, since the orphan rule prevents
+/// implementing a trait of this crate for a foreign error type.
+pub(crate) struct Registry<R> {
+    entries: Lock<Vec<(TypeId, Function<R>)>>,
+}
+
+impl<R> Registry<R> {
+    pub(crate) const fn new() -> Self {
+        Registry {
+            entries: Lock::new(Vec::new()),
+        }
+    }
+
+    /// Registers `f` for `T`, replacing the function previously registered for `T`
+    pub(crate) fn register<T: Any>(&self, f: impl Fn(&T) -> R + Send + Sync + 'static) {
+        let f: Function<R> = Arc::new(move |any: &dyn Any| f(any.downcast_ref::<T>().unwrap()));
+        self.entries.write(|entries| {
+            match entries.iter_mut().find(|(id, _)| *id == TypeId::of::<T>()) {
+                Some(entry) => entry.1 = f,
+                None => entries.push((TypeId::of::<T>(), f)),
+            }
+        });
+    }
+
+    /// Calls the function registered for the type of `value`, or `None` if there is none. The
+    /// function is called without holding the lock, so it may use the registry itself.
+    pub(crate) fn call(&self, value: &dyn Any) -> Option<R> {
+        let f = self.entries.read(|entries| {
+            entries
+                .iter()
+                .find(|(id, _)| *id == value.type_id())
+                .map(|(_, f)| f.clone())
+        })?;
+        Some(f(value))
+    }
+}
@@ -0,0 +1,789 @@
+use alloc::{string::String, sync::Arc, vec::Vec};
+#[cfg(feature = "std")]
+use core::cell::RefCell;
+use core::error::Error;
+use core::fmt;
+#[cfg(feature = "std")]
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::Theme;
+use crate::sync::Lock;
+
+/// Configures how an [`ErrorUnion`](crate::ErrorUnion) report, its `Debug` output, is rendered.
+/// Set globally with [`set_report_config`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportConfig {
+    caused_by: bool,
+    separator: &'static str,
+    alternate_sources: bool,
+    #[cfg(all(feature = "location", feature = "std"))]
+    source_snippets: bool,
+    color: Option<bool>,
+    theme: Theme,
+    #[cfg(feature = "std")]
+    hyperlinks: Option<&'static str>,
+    #[cfg(feature = "backtrace")]
+    backtrace: crate::BacktraceConfig,
+}
+
+impl ReportConfig {
+    pub const fn new() -> Self {
+        ReportConfig {
+            caused_by: true,
+            separator: ": ",
+            alternate_sources: true,
+            #[cfg(all(feature = "location", feature = "std"))]
+            source_snippets: cfg!(debug_assertions),
+            color: None,
+            theme: Theme::new(),
+            #[cfg(feature = "std")]
+            hyperlinks: None,
+            #[cfg(feature = "backtrace")]
+            backtrace: crate::BacktraceConfig::new(),
+        }
+    }
+
+    /// Opens the file of a location, e.g. in a file manager or the default editor
+    #[cfg(feature = "std")]
+    pub const FILE_HYPERLINKS: &'static str = "file://{path}";
+
+    /// Opens the file of a location at the line and column in VS Code
+    #[cfg(feature = "std")]
+    pub const VSCODE_HYPERLINKS: &'static str = "vscode://file/{path}:{line}:{column}";
+
+    /// If the report includes a numbered `Caused by:` section from the `source()` chain of the error.
+    /// Defaults to `true`.
+    pub const fn with_caused_by(mut self, caused_by: bool) -> Self {
+        self.caused_by = caused_by;
+        self
+    }
+
+    pub const fn caused_by(&self) -> bool {
+        self.caused_by
+    }
+
+    /// What joins the context, error and sources in the alternate `Display` output, `{:#}`.
+    /// Defaults to `": "`.
+    pub const fn with_separator(mut self, separator: &'static str) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    pub const fn separator(&self) -> &'static str {
+        self.separator
+    }
+
+    /// If the alternate `Display` output, `{:#}`, includes the `source()` chain of the error.
+    /// Defaults to `true`.
+    pub const fn with_alternate_sources(mut self, alternate_sources: bool) -> Self {
+        self.alternate_sources = alternate_sources;
+        self
+    }
+
+    pub const fn alternate_sources(&self) -> bool {
+        self.alternate_sources
+    }
+
+    /// If the report shows the source lines around the location of the error and of each context,
+    /// read from disk when rendered. Defaults to `true` in debug builds, e.g. when running tests,
+    /// and `false` in release builds.
+    #[cfg(all(feature = "location", feature = "std"))]
+    pub const fn with_source_snippets(mut self, source_snippets: bool) -> Self {
+        self.source_snippets = source_snippets;
+        self
+    }
+
+    #[cfg(all(feature = "location", feature = "std"))]
+    pub const fn source_snippets(&self) -> bool {
+        self.source_snippets
+    }
+
+    /// If the report is styled with the [`Theme`] and includes hyperlinks. Defaults to `true` when
+    /// stderr is a terminal and the `NO_COLOR` env var is not set, as checked on the first
+    /// render, and always `false` without `std`.
+    pub const fn with_color(mut self, color: bool) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn color(&self) -> bool {
+        self.color.unwrap_or_else(crate::theme::auto_color)
+    }
+
+    /// The styles of the sections of the report. Defaults to [`Theme::new`], which has no styling.
+    pub const fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub const fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Renders locations, including those of backtrace frames, as OSC 8 terminal hyperlinks when
+    /// color is enabled. The URL is made from `template`, where `{path}` is replaced with the
+    /// absolute path of the file, and `{line}` and `{column}` with the position, e.g.
+    /// [`ReportConfig::FILE_HYPERLINKS`] or [`ReportConfig::VSCODE_HYPERLINKS`]. Locations whose
+    /// file is not found are not linked.
+    #[cfg(feature = "std")]
+    pub const fn with_hyperlinks(mut self, template: &'static str) -> Self {
+        self.hyperlinks = Some(template);
+        self
+    }
+
+    #[cfg(feature = "std")]
+    pub const fn hyperlinks(&self) -> Option<&'static str> {
+        self.hyperlinks
+    }
+
+    /// How the backtrace of the report is rendered. Defaults to [`BacktraceConfig::new`](crate::BacktraceConfig::new).
+    #[cfg(feature = "backtrace")]
+    pub const fn with_backtrace(mut self, backtrace: crate::BacktraceConfig) -> Self {
+        self.backtrace = backtrace;
+        self
+    }
+
+    #[cfg(feature = "backtrace")]
+    pub const fn backtrace(&self) -> &crate::BacktraceConfig {
+        &self.backtrace
+    }
+}
+
+impl Default for ReportConfig {
+    fn default() -> Self {
+        ReportConfig::new()
+    }
+}
+
+/// The global report config. The lock is only held to clone or replace the `Arc`, so renders
+/// share the config without copying it, and replaced configs are freed once no render uses them.
+static CONFIG: Lock<Option<Arc<ReportConfig>>> = Lock::new(None);
+
+/// Bumped whenever the global config is replaced, so threads know their cached config is stale
+#[cfg(feature = "std")]
+static GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    static CONFIG_OVERRIDE: RefCell<Option<Arc<ReportConfig>>> = const { RefCell::new(None) };
+    /// The global config last used on this thread and its generation
+    static CONFIG_CACHE: RefCell<Option<(usize, Arc<ReportConfig>)>> = const { RefCell::new(None) };
+}
+
+/// Sets the global report config
+pub fn set_report_config(config: ReportConfig) {
+    let config = Some(Arc::new(config));
+    // The previous config is dropped outside the lock
+    let _previous = CONFIG.write(|global| core::mem::replace(global, config));
+    #[cfg(feature = "std")]
+    GENERATION.fetch_add(1, Ordering::Release);
+}
+
+/// The report config currently in effect on this thread. This is the config set by
+/// [`with_report_config`] if inside one, otherwise the global config.
+pub fn report_config() -> Arc<ReportConfig> {
+    #[cfg(feature = "std")]
+    if let Some(config) = CONFIG_OVERRIDE.with(|c| c.borrow().clone()) {
+        return config;
+    }
+    global_config()
+}
+
+fn global_config() -> Arc<ReportConfig> {
+    if let Some(config) = CONFIG.read(|global| global.clone()) {
+        return config;
+    }
+    let config = Arc::new(ReportConfig::new());
+    CONFIG.write(|global| global.get_or_insert(config).clone())
+}
+
+/// Runs `f` with the config of [`report_config`], borrowed for a render. With `std`, the global
+/// config is cached per thread, so this does not touch the lock or the reference count unless
+/// the global config was replaced.
+pub(crate) fn with_config<R>(f: impl FnOnce(&ReportConfig) -> R) -> R {
+    #[cfg(feature = "std")]
+    {
+        let f = match CONFIG_OVERRIDE.with(|c| match c.borrow().as_deref() {
+            Some(config) => Ok(f(config)),
+            None => Err(f),
+        }) {
+            Ok(result) => return result,
+            Err(f) => f,
+        };
+        CONFIG_CACHE.with(|cache| {
+            let generation = GENERATION.load(Ordering::Acquire);
+            // A nested render, e.g. of an error held as context, keeps the config of the outer one
+            if let Ok(mut cache) = cache.try_borrow_mut()
+                && cache
+                    .as_ref()
+                    .is_none_or(|(cached, _)| *cached != generation)
+            {
+                *cache = Some((generation, global_config()));
+            }
+            match cache.borrow().as_ref() {
+                Some((_, config)) => f(config),
+                None => f(&global_config()),
+            }
+        })
+    }
+    #[cfg(not(feature = "std"))]
+    f(&global_config())
+}
+
+/// Runs `f` with `config` in effect on the current thread, overriding the global config.
+/// Useful for a single render.
+#[cfg(feature = "std")]
+pub fn with_report_config<R>(config: ReportConfig, f: impl FnOnce() -> R) -> R {
+    struct Reset(Option<Arc<ReportConfig>>);
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            CONFIG_OVERRIDE.with(|c| *c.borrow_mut() = self.0.take());
+        }
+    }
+    let _reset = Reset(CONFIG_OVERRIDE.with(|c| c.borrow_mut().replace(Arc::new(config))));
+    f()
+}
+
+/// Removes the text of each message that repeats the text of the next message, its source, when
+/// it is the whole message or follows `": "` or `separator`, e.g. `client error: connection refused`.
+/// Messages where nothing else remains are skipped.
+pub(crate) fn dedup_messages<'a>(
+    messages: &'a [String],
+    separator: &'a str,
+) -> impl Iterator<Item = &'a str> {
+    messages
+        .iter()
+        .enumerate()
+        .map(move |(i, message)| match messages.get(i + 1) {
+            Some(source) if message == source => "",
+            Some(source) => [": ", separator]
+                .into_iter()
+                .find_map(|separator| {
+                    message
+                        .strip_suffix(source.as_str())?
+                        .strip_suffix(separator)
+                })
+                .unwrap_or(message),
+            None => message.as_str(),
+        })
+        .filter(|message| !message.is_empty())
+}
+
+/// Writes a numbered `Caused by:` section for `causes`, which are expected to be in `source()` order.
+/// Messages that repeat the text of their source are de-duplicated, see [`dedup_messages`].
+pub(crate) fn write_caused_by<'a>(
+    causes: impl Iterator<Item = &'a (dyn Error + 'static)>,
+    config: &ReportConfig,
+    formatter: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    let messages: Vec<String> = causes.map(|cause| alloc::format!("{cause}")).collect();
+    let mut messages = dedup_messages(&messages, config.separator())
+        .enumerate()
+        .peekable();
+    if messages.peek().is_none() {
+        return Ok(());
+    }
+    write_heading("Caused by", config, formatter)?;
+    for (index, message) in messages {
+        writeln!(formatter, "\t{index}: {message}")?;
+    }
+    writeln!(formatter, "\n---")
+}
+
+/// Writes `file:line:column` of `location`
+#[cfg(feature = "location")]
+pub(crate) fn write_location(
+    location: &core::panic::Location<'_>,
+    config: &ReportConfig,
+    formatter: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    write_source_location(
+        location.file(),
+        location.line(),
+        Some(location.column()),
+        config,
+        formatter,
+    )?;
+    writeln!(formatter)
+}
+
+/// Writes the location the error was created at, followed by the source lines around it if
+/// [`ReportConfig::source_snippets`] is enabled. Only this location gets a snippet, so the context
+/// and annotation sections stay one line per frame. Snippets are skipped when redacting, since the
+/// source may contain sensitive data as well.
+#[cfg(feature = "location")]
+pub(crate) fn write_error_location(
+    location: &core::panic::Location<'_>,
+    config: &ReportConfig,
+    formatter: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    write_location(location, config, formatter)?;
+    #[cfg(feature = "std")]
+    if config.source_snippets() && !crate::redact::should_redact() {
+        write_source_snippet(
+            location.file(),
+            location.line(),
+            location.column(),
+            formatter,
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes a section heading, e.g. `Context:`, in the heading style of the [`Theme`]
+pub(crate) fn write_heading(
+    heading: &str,
+    config: &ReportConfig,
+    formatter: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    writeln!(formatter)?;
+    config
+        .theme()
+        .heading()
+        .paint(config.color(), formatter, |f| write!(f, "{heading}:"))?;
+    writeln!(formatter)
+}
+
+/// Writes `file:line:column` in the location style of the [`Theme`], as a hyperlink if
+/// [`ReportConfig::with_hyperlinks`] is set
+#[cfg(any(feature = "location", feature = "backtrace"))]
+pub(crate) fn write_source_location(
+    file: &str,
+    line: u32,
+    column: Option<u32>,
+    config: &ReportConfig,
+    formatter: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    let color = config.color();
+    #[cfg(feature = "std")]
+    let link = config
+        .hyperlinks()
+        .filter(|_| color)
+        .and_then(|template| hyperlink(template, file, line, column));
+    #[cfg(feature = "std")]
+    if let Some(link) = &link {
+        write!(formatter, "\x1b]8;;{link}\x1b\\")?;
+    }
+    config
+        .theme()
+        .location()
+        .paint(color, formatter, |f| match column {
+            Some(column) => write!(f, "{file}:{line}:{column}"),
+            None => write!(f, "{file}:{line}"),
+        })?;
+    #[cfg(feature = "std")]
+    if link.is_some() {
+        formatter.write_str("\x1b]8;;\x1b\\")?;
+    }
+    Ok(())
+}
+
+/// The URL of a location made from a [`ReportConfig::with_hyperlinks`] template
+#[cfg(all(feature = "std", any(feature = "location", feature = "backtrace")))]
+fn hyperlink(template: &str, file: &str, line: u32, column: Option<u32>) -> Option<String> {
+    let path = resolve_source_path(file)?;
+    let path = path.to_str()?.replace('%', "%25").replace(' ', "%20");
+    Some(
+        template
+            .replace("{path}", &path)
+            .replace("{line}", &alloc::format!("{line}"))
+            .replace("{column}", &alloc::format!("{}", column.unwrap_or(1))),
+    )
+}
+
+/// The lines before and after the line of a location, that are shown in a source snippet
+#[cfg(all(feature = "std", any(feature = "location", feature = "backtrace")))]
+const SNIPPET_LINES: usize = 2;
+
+/// Writes the lines around `line` of `file`, like rustc, with a caret under `column`. Nothing is
+/// written if the file can not be read, e.g. when running on a different machine than the one
+/// it was built on.
+#[cfg(all(feature = "std", any(feature = "location", feature = "backtrace")))]
+pub(crate) fn write_source_snippet(
+    file: &str,
+    line: u32,
+    column: u32,
+    formatter: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    let Some(source) = read_source(file) else {
+        return Ok(());
+    };
+    let lines: Vec<&str> = source.lines().collect();
+    let line = line as usize;
+    if line == 0 || line > lines.len() {
+        return Ok(());
+    }
+    let first = line.saturating_sub(SNIPPET_LINES).max(1);
+    let last = (line + SNIPPET_LINES).min(lines.len());
+    let width = last.ilog10() as usize + 1;
+    writeln!(formatter, "{:width$} |", "")?;
+    for (number, text) in (first..=last).zip(&lines[first - 1..last]) {
+        if text.is_empty() {
+            writeln!(formatter, "{number:>width$} |")?;
+        } else {
+            writeln!(formatter, "{number:>width$} | {text}")?;
+        }
+        if number == line {
+            // Keep tabs, so the caret lines up with the column
+            let indent: String = text
+                .chars()
+                .take(column.saturating_sub(1) as usize)
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            writeln!(formatter, "{:width$} | {indent}^", "")?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads the source file of a location, see [`resolve_source_path`]
+#[cfg(all(feature = "std", any(feature = "location", feature = "backtrace")))]
+fn read_source(file: &str) -> Option<String> {
+    std::fs::read_to_string(resolve_source_path(file)?).ok()
+}
+
+/// The absolute path of the source file of a location, if it exists. Relative paths are relative
+/// to the directory cargo was run in, e.g. the workspace root, so the ancestors of the current
+/// directory are tried as well.
+#[cfg(all(feature = "std", any(feature = "location", feature = "backtrace")))]
+fn resolve_source_path(file: &str) -> Option<std::path::PathBuf> {
+    use std::path::{Component, Path, PathBuf};
+
+    let path = Path::new(file);
+    if path.is_absolute() {
+        return path.is_file().then(|| path.to_path_buf());
+    }
+    let path: PathBuf = path
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .collect();
+    let current_dir = std::env::current_dir().ok()?;
+    current_dir
+        .ancestors()
+        .map(|dir| dir.join(&path))
+        .find(|path| path.is_file())
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::ErrorUnion;
+
+    #[derive(Debug)]
+    struct Layer {
+        message: &'static str,
+        source: Option<Box<Layer>>,
+    }
+
+    impl Layer {
+        fn new(messages: &[&'static str]) -> Layer {
+            let (message, rest) = messages.split_first().unwrap();
+            Layer {
+                message,
+                source: (!rest.is_empty()).then(|| Box::new(Layer::new(rest))),
+            }
+        }
+    }
+
+    impl fmt::Display for Layer {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.message)
+        }
+    }
+
+    impl Error for Layer {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            self.source.as_deref().map(|e| e as &(dyn Error + 'static))
+        }
+    }
+
+    fn request_error() -> ErrorUnion<(Layer,)> {
+        ErrorUnion::new(Layer::new(&[
+            "error sending request",
+            "client error (Connect): connection refused",
+            "connection refused",
+            "connection refused",
+            "os error 111",
+        ]))
+    }
+
+    #[test]
+    fn caused_by_is_numbered_and_deduplicated() {
+        let report = format!("{:#?}", request_error());
+        assert!(
+            report.contains(
+                "\nCaused by:\n\t0: client error (Connect)\n\t1: connection refused\n\t2: os error 111\n"
+            ),
+            "{report}"
+        );
+        assert!(!report.contains("\t3:"), "{report}");
+    }
+
+    #[test]
+    fn only_whole_repeated_messages_are_deduplicated() {
+        let union: ErrorUnion<(Layer,)> =
+            ErrorUnion::new(Layer::new(&["file not found", "not found"]));
+        let report = format!("{:#?}", union);
+        assert!(
+            report.contains("\nCaused by:\n\t0: not found\n"),
+            "{report}"
+        );
+        assert_eq!(format!("{union:#}"), "file not found: not found");
+
+        let union: ErrorUnion<(Layer,)> =
+            ErrorUnion::new(Layer::new(&["reading config - not found", "not found"]));
+        let report = with_report_config(ReportConfig::new().with_separator(" - "), || {
+            format!("{union:#}")
+        });
+        assert_eq!(report, "reading config - not found");
+    }
+
+    #[test]
+    fn no_caused_by_without_source() {
+        let union: ErrorUnion<(Layer,)> = ErrorUnion::new(Layer::new(&["leaf"]));
+        let report = format!("{:#?}", union);
+        assert!(!report.contains("\nCaused by:"), "{report}");
+    }
+
+    #[test]
+    fn caused_by_can_be_switched_off() {
+        let config = ReportConfig::new().with_caused_by(false);
+        let report = with_report_config(config.clone(), || {
+            assert_eq!(*report_config(), config);
+            assert!(Arc::ptr_eq(&report_config(), &report_config()));
+            format!("{:#?}", request_error())
+        });
+        assert!(!report.contains("Caused by:"), "{report}");
+        assert!(report_config().caused_by());
+    }
+
+    #[test]
+    fn renders_borrow_the_config() {
+        assert!(with_config(|outer| with_config(|inner| core::ptr::eq(
+            outer, inner
+        ))));
+        let config = ReportConfig::new().with_caused_by(false);
+        with_report_config(config.clone(), || {
+            with_config(|current| assert_eq!(*current, config))
+        });
+    }
+
+    #[cfg(feature = "context")]
+    #[test]
+    fn caused_by_comes_before_context() {
+        let report = format!("{:#?}", request_error().context("fetching users"));
+        let caused_by = report.find("Caused by:").unwrap();
+        let context = report.find("Context:").unwrap();
+        assert!(caused_by < context, "{report}");
+    }
+
+    #[cfg(feature = "anyhow")]
+    #[test]
+    fn caused_by_in_anyhow_path() {
+        let error = anyhow::Error::new(Layer::new(&["connection refused", "os error 111"]))
+            .context("error sending request");
+        let union = ErrorUnion::anyhow(error);
+
+        let report = format!("{:#?}", union);
+        assert!(
+            report.starts_with("error sending request\n")
+                || report.contains("\nerror sending request\n"),
+            "{report}"
+        );
+        assert!(
+            report.contains("\nCaused by:\n\t0: connection refused\n\t1: os error 111\n"),
+            "{report}"
+        );
+
+        let report = with_report_config(ReportConfig::new().with_caused_by(false), || {
+            format!("{:#?}", union)
+        });
+        assert!(!report.contains("Caused by:"), "{report}");
+        assert!(report.contains("os error 111"), "{report}");
+    }
+
+    #[test]
+    fn alternate_display_joins_error_and_sources() {
+        let union = request_error();
+        assert_eq!(format!("{}", union), "error sending request");
+        assert_eq!(
+            format!("{:#}", union),
+            "error sending request: client error (Connect): connection refused: os error 111"
+        );
+    }
+
+    #[test]
+    fn alternate_display_separator_and_sources_are_configurable() {
+        let config = ReportConfig::new().with_separator(" <- ");
+        let output = with_report_config(config, || format!("{:#}", request_error()));
+        assert_eq!(
+            output,
+            "error sending request <- client error (Connect) <- connection refused <- os error 111"
+        );
+
+        let config = ReportConfig::new().with_alternate_sources(false);
+        let output = with_report_config(config, || format!("{:#}", request_error()));
+        assert_eq!(output, "error sending request");
+    }
+
+    #[cfg(feature = "context")]
+    #[test]
+    fn alternate_display_includes_context_latest_first() {
+        let union = request_error()
+            .context("fetching users")
+            .help("check the network")
+            .context("loading dashboard");
+        let output = with_report_config(ReportConfig::new().with_alternate_sources(false), || {
+            format!("{:#}", union)
+        });
+        assert_eq!(
+            output,
+            "loading dashboard: fetching users: error sending request"
+        );
+
+        let union = ErrorUnion::erase(union);
+        let output = with_report_config(ReportConfig::new().with_alternate_sources(false), || {
+            format!("{:#}", union)
+        });
+        assert_eq!(
+            output,
+            "loading dashboard: fetching users: error sending request"
+        );
+        assert_eq!(format!("{}", union), "error sending request");
+    }
+
+    #[test]
+    fn compact_debug_is_single_line() {
+        let union: ErrorUnion<(Layer,)> = ErrorUnion::new(Layer::new(&["leaf"]));
+        #[cfg(feature = "context")]
+        let union = union.context("loading").help("retry");
+        let output = format!("{:?}", union);
+        assert!(!output.contains('\n'), "{output}");
+        assert!(!output.contains("Backtrace"), "{output}");
+        #[cfg(feature = "context")]
+        assert_eq!(
+            output,
+            r#"ErrorUnion { error: Layer { message: "leaf", source: None }, context: ["loading"], annotations: ["help: retry"] }"#
+        );
+        #[cfg(not(feature = "context"))]
+        assert_eq!(
+            output,
+            r#"ErrorUnion { error: Layer { message: "leaf", source: None } }"#
+        );
+    }
+
+    #[test]
+    fn compact_debug_in_result() {
+        let result: Result<(), ErrorUnion<(Layer,)>> = Err(ErrorUnion::new(Layer::new(&["leaf"])));
+        let output = format!("{:?}", result);
+        assert!(
+            output.starts_with("Err(ErrorUnion { error: Layer"),
+            "{output}"
+        );
+        assert!(!output.contains('\n'), "{output}");
+    }
+
+    #[cfg(feature = "location")]
+    fn report_with_snippets(union: &ErrorUnion<(Layer,)>, source_snippets: bool) -> String {
+        let config = ReportConfig::new().with_source_snippets(source_snippets);
+        crate::with_redaction_policy(crate::RedactionPolicy::Reveal, || {
+            with_report_config(config, || format!("{:#?}", union))
+        })
+    }
+
+    #[cfg(feature = "location")]
+    #[test]
+    fn source_snippet_points_at_location() {
+        let line = line!() + 1;
+        let union: ErrorUnion<(Layer,)> = ErrorUnion::new(Layer::new(&["leaf"]));
+        let report = report_with_snippets(&union, true);
+        let lines: Vec<&str> = report.lines().collect();
+        let source = lines
+            .iter()
+            .position(|l| l.starts_with(&format!("{line} | ")))
+            .unwrap_or_else(|| panic!("{report}"));
+        assert!(lines[source - 2].starts_with(&format!("{} | ", line - 2)));
+        assert!(lines[source + 2].starts_with(&format!("{} | ", line + 1)));
+        let caret = lines[source + 1].find('^').unwrap();
+        assert_eq!(&lines[source][caret..caret + 15], "ErrorUnion::new");
+    }
+
+    #[cfg(feature = "location")]
+    #[test]
+    fn context_locations_have_no_snippet() {
+        let union: ErrorUnion<(Layer,)> = ErrorUnion::new(Layer::new(&["leaf"]));
+        let report = report_with_snippets(&union.context("loading").note("run init"), true);
+        let after_context = report
+            .split("Context:")
+            .nth(1)
+            .unwrap_or_else(|| panic!("{report}"));
+        assert!(!after_context.contains(" | "), "{report}");
+    }
+
+    #[cfg(feature = "location")]
+    #[test]
+    fn source_snippets_can_be_switched_off() {
+        let line = line!() + 1;
+        let union: ErrorUnion<(Layer,)> = ErrorUnion::new(Layer::new(&["leaf"]));
+        let report = report_with_snippets(&union, false);
+        assert!(report.contains(&format!("{}:{line}:", file!())), "{report}");
+        assert!(!report.contains(&format!("{line} | ")), "{report}");
+    }
+
+    #[test]
+    fn theme_is_only_used_with_color() {
+        let union = request_error();
+        let config = ReportConfig::new().with_theme(crate::Theme::classic());
+        let plain =
+            with_report_config(config.clone().with_color(false), || format!("{:#?}", union));
+        assert!(!plain.contains('\x1b'), "{plain}");
+
+        let colored = with_report_config(config.with_color(true), || format!("{:#?}", union));
+        assert!(colored.contains("\x1b[1mCaused by:\x1b[0m"), "{colored}");
+        assert!(colored.contains("\x1b[1;31mLayer {"), "{colored}");
+    }
+
+    #[cfg(feature = "location")]
+    #[test]
+    fn locations_are_hyperlinked() {
+        let union: ErrorUnion<(Layer,)> = ErrorUnion::new(Layer::new(&["leaf"]));
+        let line = line!() - 1;
+        let config = ReportConfig::new()
+            .with_source_snippets(false)
+            .with_hyperlinks(ReportConfig::VSCODE_HYPERLINKS);
+        let linked =
+            with_report_config(config.clone().with_color(true), || format!("{:#?}", union));
+        let path = resolve_source_path(file!()).unwrap();
+        let expected = format!("\x1b]8;;vscode://file/{}:{line}:", path.to_str().unwrap());
+        assert!(linked.contains(&expected), "{linked}");
+        assert!(path.is_absolute());
+
+        let unlinked = with_report_config(config.with_color(false), || format!("{:#?}", union));
+        assert!(!unlinked.contains('\x1b'), "{unlinked}");
+    }
+
+    #[cfg(any(feature = "location", feature = "backtrace"))]
+    #[test]
+    fn hyperlink_of_missing_file_is_none() {
+        assert_eq!(
+            hyperlink(ReportConfig::FILE_HYPERLINKS, "does/not/exist.rs", 1, None),
+            None
+        );
+        let link = hyperlink(ReportConfig::FILE_HYPERLINKS, file!(), 1, None).unwrap();
+        assert!(link.starts_with("file:///"), "{link}");
+        assert!(link.ends_with("src/report.rs"), "{link}");
+    }
+
+    #[cfg(any(feature = "location", feature = "backtrace"))]
+    #[test]
+    fn source_snippet_of_missing_file_is_empty() {
+        struct Snippet(&'static str);
+        impl fmt::Display for Snippet {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write_source_snippet(self.0, 1, 1, f)
+            }
+        }
+        assert_eq!(Snippet("does/not/exist.rs").to_string(), "");
+        assert_eq!(Snippet(file!()).to_string().lines().count(), 5);
+    }
+}
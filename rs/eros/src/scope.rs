@@ -0,0 +1,672 @@
+use alloc::{borrow::Cow, boxed::Box, string::String, sync::Arc, vec::Vec};
+use core::fmt::{self, Display};
+use core::future::Future;
+use core::marker::PhantomData;
+use core::pin::Pin;
+use core::ptr;
+use core::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+use core::task::{Context, Poll};
+
+use crate::context::{ContextFrame, ContextSource, FrameKind, Frames};
+use crate::sync::Lock;
+
+/// The context of a scope, see [`scope`]. Cheap to clone, since it is added to every error
+/// created while the scope is active.
+#[derive(Clone)]
+pub struct ScopeContext(Repr);
+
+#[derive(Clone)]
+enum Repr {
+    Static(&'static str),
+    Shared(Arc<dyn Display + Send + Sync>),
+    Lent(Arc<Lent>),
+}
+
+impl ScopeContext {
+    /// Context from a value, e.g. an id or a path, that is only formatted when an error created
+    /// in the scope is rendered
+    pub fn display<D: Display + Send + Sync + 'static>(value: D) -> Self {
+        ScopeContext(Repr::Shared(Arc::new(value)))
+    }
+
+    fn to_source(&self) -> ContextSource {
+        match &self.0 {
+            Repr::Static(s) => ContextSource::Static(s),
+            Repr::Shared(d) => ContextSource::Display(Box::new(d.clone())),
+            // The borrow ends with the scope, so the context is formatted now
+            Repr::Lent(lent) => ContextSource::Owned(alloc::format!("{}", lent)),
+        }
+    }
+}
+
+impl Display for ScopeContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Repr::Static(s) => f.write_str(s),
+            Repr::Shared(d) => d.fmt(f),
+            Repr::Lent(lent) => lent.fmt(f),
+        }
+    }
+}
+
+/// Context formatted by a closure that may borrow, see [`scope_with`]. The closure is lent only
+/// while the scope is active, so it is formatted when an error is created in the scope and is
+/// empty afterwards.
+struct Lent {
+    /// The closure, null while it is not lent
+    context: AtomicPtr<()>,
+    fmt: unsafe fn(*const (), &mut fmt::Formatter<'_>) -> fmt::Result,
+    /// The number of calls to `fmt` in progress, which the end of a loan waits for
+    readers: AtomicUsize,
+}
+
+impl Lent {
+    fn new<C: Fn(&mut fmt::Formatter<'_>) -> fmt::Result + Sync>() -> Arc<Lent> {
+        /// # Safety
+        /// `context` must point to a `C`
+        unsafe fn call<C: Fn(&mut fmt::Formatter<'_>) -> fmt::Result>(
+            context: *const (),
+            f: &mut fmt::Formatter<'_>,
+        ) -> fmt::Result {
+            unsafe { (*context.cast::<C>())(f) }
+        }
+        Arc::new(Lent {
+            context: AtomicPtr::new(ptr::null_mut()),
+            fmt: call::<C>,
+            readers: AtomicUsize::new(0),
+        })
+    }
+
+    /// Lends `context` until the returned loan is dropped
+    ///
+    /// # Safety
+    /// `self` must have been created with [`Lent::new`] for `C`
+    unsafe fn lend<'a, C>(&'a self, context: &'a C) -> Loan<'a> {
+        self.context
+            .store(ptr::from_ref(context).cast_mut().cast(), Ordering::SeqCst);
+        Loan(self)
+    }
+}
+
+impl Display for Lent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct Read<'a>(&'a AtomicUsize);
+        impl Drop for Read<'_> {
+            fn drop(&mut self) {
+                self.0.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+        self.readers.fetch_add(1, Ordering::SeqCst);
+        let _read = Read(&self.readers);
+        let context = self.context.load(Ordering::SeqCst);
+        if context.is_null() {
+            return Ok(());
+        }
+        // SAFETY: the context is lent, and stays lent until this read ends, since the loan waits
+        // for readers after clearing it. It is `Sync`, so it may be called from any thread.
+        unsafe { (self.fmt)(context, f) }
+    }
+}
+
+/// Ends the loan of a context when dropped, see [`Lent::lend`]
+struct Loan<'a>(&'a Lent);
+
+impl Drop for Loan<'_> {
+    fn drop(&mut self) {
+        self.0.context.store(ptr::null_mut(), Ordering::SeqCst);
+        while self.0.readers.load(Ordering::SeqCst) != 0 {
+            core::hint::spin_loop();
+        }
+    }
+}
+
+impl fmt::Debug for ScopeContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ScopeContext")
+            .field(&format_args!("{}", self))
+            .finish()
+    }
+}
+
+impl From<&'static str> for ScopeContext {
+    fn from(s: &'static str) -> ScopeContext {
+        ScopeContext(Repr::Static(s))
+    }
+}
+
+impl From<String> for ScopeContext {
+    fn from(s: String) -> ScopeContext {
+        ScopeContext::display(s)
+    }
+}
+
+impl From<Cow<'static, str>> for ScopeContext {
+    fn from(s: Cow<'static, str>) -> ScopeContext {
+        match s {
+            Cow::Borrowed(s) => ScopeContext::from(s),
+            Cow::Owned(s) => ScopeContext::from(s),
+        }
+    }
+}
+
+impl From<fmt::Arguments<'_>> for ScopeContext {
+    /// Does not allocate if the arguments are a literal without placeholders
+    fn from(args: fmt::Arguments<'_>) -> ScopeContext {
+        match args.as_str() {
+            Some(s) => ScopeContext::from(s),
+            None => ScopeContext::from(alloc::fmt::format(args)),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct ScopeFrame {
+    context: ScopeContext,
+    /// Identifies the scope, frames pushed again for each poll of an [`ErrorScope`] share it
+    id: usize,
+    #[cfg(feature = "location")]
+    location: &'static core::panic::Location<'static>,
+}
+
+/// The active scopes of a thread, or whatever errors are created on, innermost last
+#[derive(Default)]
+pub struct ScopeStack {
+    frames: Vec<ScopeFrame>,
+}
+
+impl ScopeStack {
+    pub const fn new() -> Self {
+        ScopeStack { frames: Vec::new() }
+    }
+
+    /// The number of active scopes
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The context of the active scopes, innermost first
+    pub fn contexts(&self) -> impl Iterator<Item = &ScopeContext> {
+        self.frames.iter().rev().map(|frame| &frame.context)
+    }
+}
+
+/// Where the [`ScopeStack`] is kept. With `std` this is a thread local by default. Without `std`
+/// there is no default, so scopes have no effect until a storage is set with
+/// [`set_scope_storage`], e.g. one behind a critical section on a single core target.
+pub trait ScopeStorage: Send + Sync {
+    /// Calls `f` with the scope stack of the current thread, or whatever errors are created on
+    fn with_stack(&self, f: &mut dyn FnMut(&mut ScopeStack));
+}
+
+static STORAGE: Lock<Option<&'static dyn ScopeStorage>> = Lock::new(None);
+
+/// If [`STORAGE`] is set, so the thread local is used without taking the lock
+static HAS_STORAGE: AtomicBool = AtomicBool::new(false);
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    static STACK: core::cell::RefCell<ScopeStack> = const { core::cell::RefCell::new(ScopeStack::new()) };
+}
+
+/// Sets where the scope stack is kept, replacing the thread local used with `std`. This is meant
+/// to be called once at startup, before any scope is entered, since scopes entered before are
+/// not in the new storage.
+pub fn set_scope_storage(storage: &'static dyn ScopeStorage) {
+    STORAGE.write(|current| *current = Some(storage));
+    HAS_STORAGE.store(true, Ordering::Release);
+}
+
+/// Calls `f` with the current scope stack, if there is one
+fn with_stack<R>(f: impl FnOnce(&mut ScopeStack) -> R) -> Option<R> {
+    let storage = match HAS_STORAGE.load(Ordering::Acquire) {
+        true => STORAGE.read(|storage| *storage),
+        false => None,
+    };
+    if let Some(storage) = storage {
+        let mut f = Some(f);
+        let mut result = None;
+        storage.with_stack(&mut |stack| {
+            if let Some(f) = f.take() {
+                result = Some(f(stack));
+            }
+        });
+        return result;
+    }
+    #[cfg(feature = "std")]
+    {
+        STACK
+            .try_with(|stack| stack.try_borrow_mut().ok().map(|mut stack| f(&mut stack)))
+            .ok()
+            .flatten()
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        drop(f);
+        None
+    }
+}
+
+/// Pops the scope it was created for when dropped, see [`push_context`]
+#[must_use = "the scope ends when the guard is dropped"]
+pub struct ScopeGuard {
+    /// The id of the scope, `None` if there is no stack
+    id: Option<usize>,
+    // The stack is usually a thread local, so the guard must be dropped on the same thread
+    _not_send: PhantomData<*const ()>,
+}
+
+impl fmt::Debug for ScopeGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScopeGuard").finish_non_exhaustive()
+    }
+}
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        // Only this scope is removed, since guards may be dropped out of order, e.g. when moved
+        // into a struct or `mem::forget` is used on an inner guard
+        if let Some(id) = self.id {
+            with_stack(|stack| {
+                if let Some(index) = stack.frames.iter().rposition(|frame| frame.id == id) {
+                    stack.frames.remove(index);
+                }
+            });
+        }
+    }
+}
+
+/// Adds `context` to every [`ErrorUnion`](crate::ErrorUnion) created on this thread until the
+/// returned guard is dropped, e.g. "processing order 42", without threading `.context(..)`
+/// through every call in between.
+#[cfg_attr(feature = "location", track_caller)]
+pub fn push_context<C: Into<ScopeContext>>(context: C) -> ScopeGuard {
+    push_frame(ScopeFrame::new(context.into()))
+}
+
+impl ScopeFrame {
+    #[cfg_attr(feature = "location", track_caller)]
+    fn new(context: ScopeContext) -> Self {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        ScopeFrame {
+            context,
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            #[cfg(feature = "location")]
+            location: core::panic::Location::caller(),
+        }
+    }
+}
+
+fn push_frame(frame: ScopeFrame) -> ScopeGuard {
+    let id = frame.id;
+    let id = with_stack(|stack| {
+        stack.frames.push(frame);
+        id
+    });
+    ScopeGuard {
+        id,
+        _not_send: PhantomData,
+    }
+}
+
+/// Runs `f` with `context` added to every [`ErrorUnion`](crate::ErrorUnion) created on this
+/// thread inside it, see [`push_context`].
+#[cfg_attr(feature = "location", track_caller)]
+pub fn scope<C: Into<ScopeContext>, R>(context: C, f: impl FnOnce() -> R) -> R {
+    let _guard = push_context(context);
+    f()
+}
+
+/// Like [`scope`], but the context is formatted by `context`, e.g.
+/// `|f| write!(f, "loading {}", name)`, only when an error is created inside the scope. Unlike
+/// [`ScopeContext::display`], the closure may borrow, e.g. arguments of the current function.
+#[cfg_attr(feature = "location", track_caller)]
+pub fn scope_with<C, R>(context: C, f: impl FnOnce() -> R) -> R
+where
+    C: Fn(&mut fmt::Formatter<'_>) -> fmt::Result + Sync,
+{
+    let lent = Lent::new::<C>();
+    // SAFETY: `lent` was created for `C`
+    let _loan = unsafe { lent.lend(&context) };
+    let _guard = push_frame(ScopeFrame::new(ScopeContext(Repr::Lent(lent.clone()))));
+    f()
+}
+
+/// Adds ambient context to futures, see [`ScopeFutureExt::in_error_scope`]
+pub trait ScopeFutureExt: Future + Sized {
+    /// Adds `context` to every [`ErrorUnion`](crate::ErrorUnion) created while this future is
+    /// polled, like [`scope`] does for a closure. The scope is entered for each poll, on whatever
+    /// thread polls the future, so it follows the task across awaits, even with work stealing
+    /// runtimes. Guards from [`push_context`] should not be held across an await inside it.
+    #[cfg_attr(feature = "location", track_caller)]
+    fn in_error_scope<C: Into<ScopeContext>>(self, context: C) -> ErrorScope<Self> {
+        ErrorScope {
+            future: self,
+            frame: ScopeFrame::new(context.into()),
+        }
+    }
+
+    /// Like [`in_error_scope`](ScopeFutureExt::in_error_scope), but the context is formatted by
+    /// `context` only when an error is created while the future is polled, see [`scope_with`]
+    #[cfg_attr(feature = "location", track_caller)]
+    fn in_error_scope_with<C>(self, context: C) -> ErrorScopeWith<Self, C>
+    where
+        C: Fn(&mut fmt::Formatter<'_>) -> fmt::Result + Sync,
+    {
+        let lent = Lent::new::<C>();
+        ErrorScopeWith {
+            future: self,
+            context,
+            frame: ScopeFrame::new(ScopeContext(Repr::Lent(lent.clone()))),
+            lent,
+        }
+    }
+}
+
+impl<F: Future> ScopeFutureExt for F {}
+
+/// A future with ambient context, created with [`ScopeFutureExt::in_error_scope`]
+#[must_use = "futures do nothing unless polled"]
+pub struct ErrorScope<F> {
+    future: F,
+    frame: ScopeFrame,
+}
+
+impl<F: fmt::Debug> fmt::Debug for ErrorScope<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ErrorScope")
+            .field("future", &self.future)
+            .field("context", &self.frame.context)
+            .finish()
+    }
+}
+
+impl<F: Future> Future for ErrorScope<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: `future` is structurally pinned, it is never moved out of `self`, and `frame` is
+        // only read
+        let this = unsafe { self.get_unchecked_mut() };
+        let _guard = push_frame(this.frame.clone());
+        unsafe { Pin::new_unchecked(&mut this.future) }.poll(cx)
+    }
+}
+
+/// A future with ambient context, created with [`ScopeFutureExt::in_error_scope_with`]
+#[must_use = "futures do nothing unless polled"]
+pub struct ErrorScopeWith<F, C> {
+    future: F,
+    context: C,
+    lent: Arc<Lent>,
+    frame: ScopeFrame,
+}
+
+impl<F: fmt::Debug, C> fmt::Debug for ErrorScopeWith<F, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ErrorScopeWith")
+            .field("future", &self.future)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F, C> Future for ErrorScopeWith<F, C>
+where
+    F: Future,
+    C: Fn(&mut fmt::Formatter<'_>) -> fmt::Result + Sync,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: `future` is structurally pinned, it is never moved out of `self`, and the other
+        // fields are only read
+        let this = unsafe { self.get_unchecked_mut() };
+        // The context is only lent for this poll, since `self` may move between polls
+        // SAFETY: `lent` was created for `C`
+        let _loan = unsafe { this.lent.lend(&this.context) };
+        let _guard = push_frame(this.frame.clone());
+        unsafe { Pin::new_unchecked(&mut this.future) }.poll(cx)
+    }
+}
+
+/// The context frames of a new error, the active scopes innermost first
+pub(crate) fn snapshot() -> Frames {
+    with_stack(|stack| {
+        stack
+            .frames
+            .iter()
+            .rev()
+            .map(|frame| ContextFrame {
+                context: frame.context.to_source(),
+                kind: FrameKind::Context,
+                #[cfg(feature = "location")]
+                location: frame.location,
+                #[cfg(feature = "user_context")]
+                is_user_facing: false,
+                attachments: None,
+                scope: Some(frame.id),
+            })
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// Where context added now goes in `frames`, inside the scope frames whose scope is still active.
+/// Scope frames whose scope has ended are no longer tracked, so later context goes outside them.
+pub(crate) fn insertion_index(frames: &mut Frames) -> usize {
+    let start = frames
+        .iter()
+        .rposition(|frame| frame.scope.is_none())
+        .map_or(0, |index| index + 1);
+    if start == frames.len() {
+        return start;
+    }
+    let active = with_stack(|stack| {
+        frames[start..].iter().position(|frame| {
+            stack
+                .frames
+                .iter()
+                .any(|scope| frame.scope == Some(scope.id))
+        })
+    })
+    .flatten();
+    let index = active.map_or(frames.len(), |active| start + active);
+    for frame in &mut frames[start..index] {
+        frame.scope = None;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorUnion;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[derive(Debug)]
+    struct OrderError;
+
+    impl Display for OrderError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("order failed")
+        }
+    }
+
+    impl core::error::Error for OrderError {}
+
+    fn order_error() -> ErrorUnion<(OrderError,)> {
+        ErrorUnion::new(OrderError)
+    }
+
+    fn contexts(error: &ErrorUnion<(OrderError,)>) -> Vec<String> {
+        error
+            .frames()
+            .map(|frame| frame.context().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn errors_created_in_scope_capture_it() {
+        let id = 42;
+        let error = scope(format_args!("processing order {id}"), || {
+            scope("charging card", order_error)
+        });
+        assert_eq!(
+            contexts(&error),
+            vec!["charging card", "processing order 42"]
+        );
+        assert_eq!(
+            alloc::format!("{error:#}"),
+            "processing order 42: charging card: order failed"
+        );
+    }
+
+    #[test]
+    fn context_added_in_scope_is_inside_it() {
+        let error = scope("processing order", || {
+            scope("charging card", || order_error().context("loading batch"))
+                .context("retrying payment")
+        })
+        .context("handling request");
+        assert_eq!(
+            contexts(&error),
+            vec![
+                "loading batch",
+                "charging card",
+                "retrying payment",
+                "processing order",
+                "handling request"
+            ]
+        );
+        assert_eq!(
+            alloc::format!("{error:#}"),
+            "handling request: processing order: retrying payment: charging card: loading batch: \
+             order failed"
+        );
+    }
+
+    #[test]
+    fn attach_targets_context_inside_scopes() {
+        let error = scope("processing order", || {
+            order_error().context("loading batch").attach(7u32)
+        });
+        let attachments = error
+            .frames()
+            .map(|frame| frame.attachments::<u32>().count())
+            .collect::<Vec<_>>();
+        assert_eq!(contexts(&error), vec!["loading batch", "processing order"]);
+        assert_eq!(attachments, vec![1, 0]);
+    }
+
+    #[test]
+    fn guard_pops_its_scope() {
+        let outer = push_context("outer");
+        {
+            let _inner = push_context(ScopeContext::display(7));
+            let error = order_error();
+            assert_eq!(contexts(&error), vec!["7", "outer"]);
+        }
+        let error = order_error();
+        assert_eq!(contexts(&error), vec!["outer"]);
+        drop(outer);
+        let error = order_error();
+        assert!(contexts(&error).is_empty());
+    }
+
+    #[test]
+    fn guards_dropped_out_of_order_pop_only_their_scope() {
+        let outer = push_context("outer");
+        let inner = push_context("inner");
+        drop(outer);
+        assert_eq!(contexts(&order_error()), vec!["inner"]);
+        let innermost = push_context("innermost");
+        drop(inner);
+        assert_eq!(contexts(&order_error()), vec!["innermost"]);
+        drop(innermost);
+        assert!(contexts(&order_error()).is_empty());
+    }
+
+    #[test]
+    fn lent_context_is_formatted_only_for_new_errors() {
+        let formats = AtomicUsize::new(0);
+        let name = String::from("config");
+        let context = |f: &mut fmt::Formatter<'_>| {
+            formats.fetch_add(1, Ordering::Relaxed);
+            write!(f, "loading {name}")
+        };
+        scope_with(context, || ());
+        assert_eq!(formats.load(Ordering::Relaxed), 0);
+        let error = scope_with(context, order_error);
+        drop(name);
+        assert_eq!(formats.load(Ordering::Relaxed), 1);
+        assert_eq!(contexts(&error), vec!["loading config"]);
+        assert_eq!(formats.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn lent_context_is_in_scope_only_while_polled() {
+        let id = 42;
+        let mut polls = 0;
+        let future = core::future::poll_fn(|cx| {
+            polls += 1;
+            if polls == 1 {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            Poll::Ready(order_error())
+        });
+        let future = future.in_error_scope_with(|f| write!(f, "handling request {id}"));
+        let mut future = core::pin::pin!(future);
+        let mut cx = Context::from_waker(core::task::Waker::noop());
+
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+        assert!(contexts(&order_error()).is_empty());
+        let Poll::Ready(error) = future.as_mut().poll(&mut cx) else {
+            panic!("the future is ready on the second poll");
+        };
+        assert_eq!(contexts(&error), vec!["handling request 42"]);
+    }
+
+    #[cfg(feature = "location")]
+    #[test]
+    fn scope_frames_point_at_the_scope() {
+        let line = line!() + 1;
+        let error = scope("outer", order_error);
+        let frame = error.frames().next().unwrap();
+        assert_eq!(frame.location().line(), line);
+        assert_eq!(frame.location().file(), file!());
+    }
+
+    #[test]
+    fn future_is_in_scope_only_while_polled() {
+        let mut polls = 0;
+        let future = core::future::poll_fn(|cx| {
+            polls += 1;
+            if polls == 1 {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            Poll::Ready(order_error())
+        });
+        let mut future = core::pin::pin!(future.in_error_scope("handling request"));
+        let mut cx = Context::from_waker(core::task::Waker::noop());
+
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+        assert!(contexts(&order_error()).is_empty());
+        let Poll::Ready(error) = future.as_mut().poll(&mut cx) else {
+            panic!("the future is ready on the second poll");
+        };
+        assert_eq!(contexts(&error), vec!["handling request"]);
+    }
+
+    #[test]
+    fn scopes_are_per_thread() {
+        let _guard = push_context("main thread");
+        let error = std::thread::spawn(order_error).join().unwrap();
+        assert!(contexts(&error).is_empty());
+    }
+}
@@ -0,0 +1,149 @@
+use core::cell::Cell;
+
+use tracing_error::SpanTrace;
+
+use crate::policy::{CapturePolicy, PolicyCell};
+
+/// Controls when a `tracing_error::SpanTrace` is captured on creating an
+/// [`ErrorUnion`](crate::ErrorUnion). Spans are only recorded if the subscriber has a
+/// `tracing_error::ErrorLayer`, so capturing is cheap otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpanTracePolicy {
+    /// Always captures, except for expected errors, see [`register_expected`](crate::register_expected)
+    #[default]
+    Capture,
+    /// Never captures
+    Disabled,
+    /// Captures for the given percentage of errors. Expected errors, see
+    /// [`register_expected`](crate::register_expected), are skipped. Sampling is deterministic rather than
+    /// random: errors are counted across all threads and the first `percent` of every 100 are
+    /// captured.
+    Sample(u8),
+}
+
+impl CapturePolicy for SpanTracePolicy {
+    fn to_parts(self) -> (u8, u8) {
+        match self {
+            SpanTracePolicy::Capture => (0, 0),
+            SpanTracePolicy::Disabled => (1, 0),
+            SpanTracePolicy::Sample(percent) => (2, percent),
+        }
+    }
+
+    fn from_parts(kind: u8, percent: u8) -> Option<Self> {
+        match kind {
+            0 => Some(SpanTracePolicy::Capture),
+            1 => Some(SpanTracePolicy::Disabled),
+            2 => Some(SpanTracePolicy::Sample(percent)),
+            _ => None,
+        }
+    }
+}
+
+std::thread_local! {
+    static POLICY_OVERRIDE: Cell<u16> = const { Cell::new(0) };
+}
+
+static POLICY: PolicyCell = PolicyCell::new(&POLICY_OVERRIDE);
+
+/// Sets the global span trace policy
+pub fn set_span_trace_policy(policy: SpanTracePolicy) {
+    POLICY.set(policy);
+}
+
+/// The span trace policy currently in effect on this thread. This is the policy set by
+/// [`with_span_trace_policy`] if inside one, otherwise the global policy.
+pub fn span_trace_policy() -> SpanTracePolicy {
+    POLICY.get()
+}
+
+/// Runs `f` with `policy` in effect on the current thread, overriding the global policy
+pub fn with_span_trace_policy<R>(policy: SpanTracePolicy, f: impl FnOnce() -> R) -> R {
+    POLICY.with(policy, f)
+}
+
+/// Captures a span trace for a new error, according to the [`span_trace_policy`]. Errors are
+/// skipped if `expected` returns true, which is only called if the policy depends on it.
+pub(crate) fn capture(expected: impl FnOnce() -> bool) -> SpanTrace {
+    let capture = match span_trace_policy() {
+        SpanTracePolicy::Disabled => false,
+        _ if expected() => false,
+        SpanTracePolicy::Capture => true,
+        SpanTracePolicy::Sample(percent) => POLICY.sample(percent),
+    };
+    if capture {
+        SpanTrace::capture()
+    } else {
+        SpanTrace::new(tracing::Span::none())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ErrorUnion, StrError};
+    use alloc::format;
+    use tracing_error::SpanTraceStatus;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    fn in_span<R>(f: impl FnOnce() -> R) -> R {
+        let subscriber = tracing_subscriber::registry().with(tracing_error::ErrorLayer::default());
+        tracing::subscriber::with_default(subscriber, || {
+            let _request = tracing::info_span!("handle_request", id = 42).entered();
+            let _query = tracing::info_span!("query").entered();
+            f()
+        })
+    }
+
+    fn error() -> ErrorUnion<(StrError,)> {
+        ErrorUnion::new(StrError::from("timed out"))
+    }
+
+    #[test]
+    fn span_trace_is_captured_and_rendered() {
+        let error = in_span(error);
+        assert_eq!(error.span_trace().status(), SpanTraceStatus::CAPTURED);
+        let report = format!("{error:#?}");
+        let section = &report[report.find("Span trace:").expect(&report)..];
+        let query = section.find("query").expect(&report);
+        let request = section.find("handle_request").expect(&report);
+        assert!(query < request, "{report}");
+        assert!(section.contains("id=42"), "{report}");
+    }
+
+    #[test]
+    fn span_trace_is_not_captured_when_disabled() {
+        let error = with_span_trace_policy(SpanTracePolicy::Disabled, || in_span(error));
+        assert_ne!(error.span_trace().status(), SpanTraceStatus::CAPTURED);
+        assert!(!format!("{error:#?}").contains("Span trace:"));
+
+        let error = in_span(|| error.force_span_trace());
+        assert_eq!(error.span_trace().status(), SpanTraceStatus::CAPTURED);
+    }
+
+    #[test]
+    fn span_trace_is_not_captured_for_expected_errors() {
+        #[derive(Debug)]
+        struct NotFound;
+        impl core::fmt::Display for NotFound {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.write_str("not found")
+            }
+        }
+        impl core::error::Error for NotFound {}
+
+        let error: ErrorUnion<(NotFound,)> = in_span(|| ErrorUnion::expected(NotFound));
+        assert_ne!(error.span_trace().status(), SpanTraceStatus::CAPTURED);
+
+        crate::register_expected::<NotFound>();
+        let error: ErrorUnion<(NotFound,)> = in_span(|| ErrorUnion::new(NotFound));
+        assert_ne!(error.span_trace().status(), SpanTraceStatus::CAPTURED);
+    }
+
+    #[test]
+    fn span_trace_is_empty_outside_spans() {
+        let error = error();
+        assert_ne!(error.span_trace().status(), SpanTraceStatus::CAPTURED);
+        assert!(!format!("{error:#?}").contains("Span trace:"));
+    }
+}
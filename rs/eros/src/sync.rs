@@ -0,0 +1,60 @@
+/// A reader-writer lock for global state. With `std` this is a `std::sync::RwLock`, otherwise a
+/// spin lock, so the lock must only be held briefly, e.g. to clone or replace an `Arc`.
+pub(crate) struct Lock<T> {
+    #[cfg(feature = "std")]
+    inner: std::sync::RwLock<T>,
+    #[cfg(not(feature = "std"))]
+    locked: core::sync::atomic::AtomicBool,
+    #[cfg(not(feature = "std"))]
+    inner: core::cell::UnsafeCell<T>,
+}
+
+// SAFETY: without `std`, `inner` is only accessed while holding `locked`
+#[cfg(not(feature = "std"))]
+unsafe impl<T: Send> Sync for Lock<T> {}
+
+impl<T> Lock<T> {
+    pub(crate) const fn new(value: T) -> Self {
+        Lock {
+            #[cfg(feature = "std")]
+            inner: std::sync::RwLock::new(value),
+            #[cfg(not(feature = "std"))]
+            locked: core::sync::atomic::AtomicBool::new(false),
+            #[cfg(not(feature = "std"))]
+            inner: core::cell::UnsafeCell::new(value),
+        }
+    }
+
+    pub(crate) fn read<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        #[cfg(feature = "std")]
+        return f(&self.inner.read().unwrap_or_else(|e| e.into_inner()));
+        #[cfg(not(feature = "std"))]
+        return self.write(|value| f(value));
+    }
+
+    pub(crate) fn write<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        #[cfg(feature = "std")]
+        return f(&mut self.inner.write().unwrap_or_else(|e| e.into_inner()));
+        #[cfg(not(feature = "std"))]
+        {
+            use core::sync::atomic::Ordering;
+
+            struct Unlock<'a>(&'a core::sync::atomic::AtomicBool);
+            impl Drop for Unlock<'_> {
+                fn drop(&mut self) {
+                    self.0.store(false, Ordering::Release);
+                }
+            }
+            while self
+                .locked
+                .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_err()
+            {
+                core::hint::spin_loop();
+            }
+            let _unlock = Unlock(&self.locked);
+            // SAFETY: the lock is held until `_unlock` is dropped, even if `f` panics
+            f(unsafe { &mut *self.inner.get() })
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use core::fmt;
+
+/// A terminal color of a [`Style`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    /// A color of the 256 color ANSI palette
+    Fixed(u8),
+}
+
+/// How a part of a report is styled, written as ANSI escape codes when color is enabled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Style {
+    fg: Option<Color>,
+    bold: bool,
+    dimmed: bool,
+    italic: bool,
+    underline: bool,
+}
+
+impl Style {
+    /// No styling
+    pub const fn new() -> Self {
+        Style {
+            fg: None,
+            bold: false,
+            dimmed: false,
+            italic: false,
+            underline: false,
+        }
+    }
+
+    pub const fn fg(mut self, color: Color) -> Self {
+        self.fg = Some(color);
+        self
+    }
+
+    pub const fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    pub const fn dimmed(mut self) -> Self {
+        self.dimmed = true;
+        self
+    }
+
+    pub const fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
+    pub const fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    pub const fn fg_color(&self) -> Option<Color> {
+        self.fg
+    }
+
+    pub const fn is_bold(&self) -> bool {
+        self.bold
+    }
+
+    pub const fn is_plain(&self) -> bool {
+        self.fg.is_none() && !self.bold && !self.dimmed && !self.italic && !self.underline
+    }
+
+    /// Writes what `write` writes in this style, if `color` is enabled
+    pub(crate) fn paint(
+        &self,
+        color: bool,
+        formatter: &mut fmt::Formatter<'_>,
+        write: impl FnOnce(&mut fmt::Formatter<'_>) -> fmt::Result,
+    ) -> fmt::Result {
+        if !color || self.is_plain() {
+            return write(formatter);
+        }
+        let mut codes = [self.bold, self.dimmed, self.italic, self.underline]
+            .into_iter()
+            .zip(1..)
+            .filter_map(|(enabled, code)| enabled.then_some(code));
+        formatter.write_str("\x1b[")?;
+        let mut separator = "";
+        for code in &mut codes {
+            write!(formatter, "{separator}{code}")?;
+            separator = ";";
+        }
+        match self.fg {
+            Some(Color::Fixed(color)) => write!(formatter, "{separator}38;5;{color}")?,
+            Some(color) => write!(formatter, "{separator}{}", 30 + color.as_u8())?,
+            None => {}
+        }
+        formatter.write_str("m")?;
+        write(formatter)?;
+        formatter.write_str("\x1b[0m")
+    }
+}
+
+impl Color {
+    const fn as_u8(self) -> u8 {
+        match self {
+            Color::Black => 0,
+            Color::Red => 1,
+            Color::Green => 2,
+            Color::Yellow => 3,
+            Color::Blue => 4,
+            Color::Magenta => 5,
+            Color::Cyan => 6,
+            Color::White => 7,
+            Color::Fixed(color) => color,
+        }
+    }
+}
+
+/// The styles of the sections of a report. Only used when color is enabled, see
+/// [`ReportConfig::with_color`](crate::ReportConfig::with_color).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    error: Style,
+    heading: Style,
+    context: Style,
+    location: Style,
+    backtrace: Style,
+}
+
+impl Theme {
+    /// No styling, the default
+    pub const fn new() -> Self {
+        Theme {
+            error: Style::new(),
+            heading: Style::new(),
+            context: Style::new(),
+            location: Style::new(),
+            backtrace: Style::new(),
+        }
+    }
+
+    /// Red errors, bold headings, cyan context and dimmed locations
+    pub const fn classic() -> Self {
+        Theme {
+            error: Style::new().fg(Color::Red).bold(),
+            heading: Style::new().bold(),
+            context: Style::new().fg(Color::Cyan),
+            location: Style::new().dimmed(),
+            backtrace: Style::new().fg(Color::Red),
+        }
+    }
+
+    /// The error itself
+    pub const fn with_error(mut self, style: Style) -> Self {
+        self.error = style;
+        self
+    }
+
+    pub const fn error(&self) -> Style {
+        self.error
+    }
+
+    /// Section headings, e.g. `Context:`
+    pub const fn with_heading(mut self, style: Style) -> Self {
+        self.heading = style;
+        self
+    }
+
+    pub const fn heading(&self) -> Style {
+        self.heading
+    }
+
+    /// Context and annotations
+    pub const fn with_context(mut self, style: Style) -> Self {
+        self.context = style;
+        self
+    }
+
+    pub const fn context(&self) -> Style {
+        self.context
+    }
+
+    /// Locations, e.g. `src/main.rs:10:5`
+    pub const fn with_location(mut self, style: Style) -> Self {
+        self.location = style;
+        self
+    }
+
+    pub const fn location(&self) -> Style {
+        self.location
+    }
+
+    /// The function names of backtrace frames
+    pub const fn with_backtrace(mut self, style: Style) -> Self {
+        self.backtrace = style;
+        self
+    }
+
+    pub const fn backtrace(&self) -> Style {
+        self.backtrace
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::new()
+    }
+}
+
+/// If color is enabled by default. This is when stderr, where errors are usually written, is a
+/// terminal and the `NO_COLOR` env var is not set. Checked once, on the first call, since it is
+/// needed for every styled section of a report. Always `false` without `std`.
+pub(crate) fn auto_color() -> bool {
+    #[cfg(feature = "std")]
+    {
+        use std::io::IsTerminal;
+
+        static AUTO_COLOR: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+        *AUTO_COLOR.get_or_init(|| {
+            std::env::var_os("NO_COLOR").is_none_or(|no_color| no_color.is_empty())
+                && std::io::stderr().is_terminal()
+        })
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::{String, ToString};
+
+    struct Painted(Style, bool, &'static str);
+
+    impl fmt::Display for Painted {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.0.paint(self.1, f, |f| f.write_str(self.2))
+        }
+    }
+
+    fn painted(style: Style, color: bool) -> String {
+        Painted(style, color, "text").to_string()
+    }
+
+    #[test]
+    fn style_is_written_as_ansi_codes() {
+        let style = Style::new().fg(Color::Red).bold();
+        assert_eq!(painted(style, true), "\x1b[1;31mtext\x1b[0m");
+        assert_eq!(
+            painted(Style::new().dimmed().underline(), true),
+            "\x1b[2;4mtext\x1b[0m"
+        );
+        assert_eq!(
+            painted(Style::new().fg(Color::Fixed(208)), true),
+            "\x1b[38;5;208mtext\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn plain_or_disabled_style_writes_nothing_extra() {
+        assert_eq!(painted(Style::new(), true), "text");
+        assert_eq!(painted(Style::new().fg(Color::Red).bold(), false), "text");
+    }
+}
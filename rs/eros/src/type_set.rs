@@ -5,7 +5,7 @@ use core::fmt;
 use std::backtrace::Backtrace;
 
 #[cfg(feature = "context")]
-use crate::context::ErosContext;
+use crate::context::{ContextFrame, FrameKind};
 use crate::{AnyError, SendSyncError};
 
 /* ------------------------- Helpers ----------------------- */
@@ -79,33 +79,87 @@ impl fmt::Display for End {
 }
 
 pub trait DisplayFold {
-    fn display_fold(any: &dyn Any, formatter: &mut fmt::Formatter<'_>) -> fmt::Result;
+    fn display_fold(
+        any: &dyn SendSyncError,
+        formatter: &mut fmt::Formatter<'_>,
+        #[cfg(feature = "context")] context: &[ContextFrame],
+    ) -> fmt::Result;
 }
 
 impl DisplayFold for End {
-    fn display_fold(_: &dyn Any, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fn display_fold(
+        _: &dyn SendSyncError,
+        _: &mut fmt::Formatter<'_>,
+        #[cfg(feature = "context")] _context: &[ContextFrame],
+    ) -> fmt::Result {
         unreachable!("display_fold called on End");
     }
 }
 
-pub(crate) fn write_display<T: fmt::Display + ?Sized>(
+/// Writes the error. With the alternate flag, `{:#}`, the context frames are written first, latest first,
+/// then the error, then optionally its sources, all joined by the configured separator.
+/// e.g. `outer context: inner context: error: source`
+pub(crate) fn write_display<T: SendSyncError + ?Sized>(
     t: &T,
     formatter: &mut fmt::Formatter<'_>,
+    #[cfg(feature = "context")] context: &[ContextFrame],
 ) -> fmt::Result {
-    t.fmt(formatter)
+    if !formatter.alternate() {
+        return fmt::Display::fmt(t, formatter);
+    }
+    crate::report::with_config(|config| {
+        let separator = config.separator();
+        #[cfg(feature = "context")]
+        for frame in context
+            .iter()
+            .rev()
+            .filter(|frame| frame.kind == FrameKind::Context)
+        {
+            write!(formatter, "{}{separator}", frame.context)?;
+        }
+        if !config.alternate_sources() {
+            return write!(formatter, "{t}");
+        }
+        let mut messages = alloc::vec![alloc::format!("{t}")];
+        messages.extend(
+            core::iter::successors(t.source(), |&source| source.source())
+                .map(|source| alloc::format!("{source}")),
+        );
+        for (i, message) in crate::report::dedup_messages(&messages, separator).enumerate() {
+            if i != 0 {
+                formatter.write_str(separator)?;
+            }
+            formatter.write_str(message)?;
+        }
+        Ok(())
+    })
 }
 
 impl<Head, Tail> DisplayFold for Cons<Head, Tail>
 where
     Cons<Head, Tail>: fmt::Display,
-    Head: 'static + fmt::Display,
+    Head: SendSyncError,
     Tail: DisplayFold,
 {
-    fn display_fold(any: &dyn Any, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if let Some(head_ref) = any.downcast_ref::<Head>() {
-            write_display(head_ref, formatter)
+    fn display_fold(
+        any: &dyn SendSyncError,
+        formatter: &mut fmt::Formatter<'_>,
+        #[cfg(feature = "context")] context: &[ContextFrame],
+    ) -> fmt::Result {
+        if let Some(head_ref) = (any as &dyn Any).downcast_ref::<Head>() {
+            write_display(
+                head_ref,
+                formatter,
+                #[cfg(feature = "context")]
+                context,
+            )
         } else {
-            Tail::display_fold(any, formatter)
+            Tail::display_fold(
+                any,
+                formatter,
+                #[cfg(feature = "context")]
+                context,
+            )
         }
     }
 }
@@ -116,9 +170,11 @@ pub trait DebugFold {
     fn debug_fold(
         any: &dyn SendSyncError,
         formatter: &mut fmt::Formatter<'_>,
-        #[cfg(feature = "context")] context: &[ErosContext],
+        #[cfg(feature = "context")] context: &[ContextFrame],
         #[cfg(feature = "backtrace")] backtrace: &Backtrace,
         #[cfg(feature = "location")] location: &'static core::panic::Location<'static>,
+        #[cfg(feature = "return_trace")] return_trace: &[&'static core::panic::Location<'static>],
+        #[cfg(feature = "span_trace")] span_trace: &tracing_error::SpanTrace,
     ) -> fmt::Result;
 }
 
@@ -126,9 +182,13 @@ impl DebugFold for End {
     fn debug_fold(
         _: &dyn SendSyncError,
         _: &mut fmt::Formatter<'_>,
-        #[cfg(feature = "context")] _context: &[ErosContext],
+        #[cfg(feature = "context")] _context: &[ContextFrame],
         #[cfg(feature = "backtrace")] _backtrace: &Backtrace,
         #[cfg(feature = "location")] _location: &'static core::panic::Location<'static>,
+        #[cfg(feature = "return_trace")] _return_trace: &[&'static core::panic::Location<
+            'static,
+        >],
+        #[cfg(feature = "span_trace")] _span_trace: &tracing_error::SpanTrace,
     ) -> fmt::Result {
         unreachable!("debug_fold called on End");
     }
@@ -137,128 +197,311 @@ impl DebugFold for End {
 pub(crate) fn write_debug<T: SendSyncError + ?Sized>(
     t: &T,
     formatter: &mut fmt::Formatter<'_>,
-    #[cfg(feature = "context")] context: &[ErosContext],
+    #[cfg(feature = "context")] context: &[ContextFrame],
     #[cfg(feature = "backtrace")] backtrace: &Backtrace,
     #[cfg(feature = "location")] location: &'static core::panic::Location<'static>,
+    #[cfg(feature = "return_trace")] return_trace: &[&'static core::panic::Location<'static>],
+    #[cfg(feature = "span_trace")] span_trace: &tracing_error::SpanTrace,
 ) -> fmt::Result {
+    #[cfg(feature = "context")]
+    fn write_context_item(
+        item: &dyn fmt::Display,
+        config: &crate::ReportConfig,
+        formatter: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        formatter.write_str("\t- ")?;
+        config
+            .theme()
+            .context()
+            .paint(config.color(), formatter, |f| write!(f, "{item}"))?;
+        writeln!(formatter)
+    }
     #[cfg(feature = "context")]
     fn write_eros_context(
-        context: &ErosContext,
+        context: &ContextFrame,
+        config: &crate::ReportConfig,
         formatter: &mut fmt::Formatter<'_>,
     ) -> fmt::Result {
         #[cfg(feature = "location")]
+        crate::report::write_location(context.location, config, formatter)?;
+        write_context_item(&context.context, config, formatter)
+    }
+    #[cfg(feature = "context")]
+    fn write_annotations(
+        context: &[ContextFrame],
+        config: &crate::ReportConfig,
+        formatter: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        for kind in FrameKind::ANNOTATIONS {
+            let mut annotations = context.iter().filter(|frame| frame.kind == kind).peekable();
+            if annotations.peek().is_none() {
+                continue;
+            }
+            let heading = match kind {
+                FrameKind::Warning => "Warning",
+                FrameKind::Note => "Note",
+                FrameKind::Help => "Help",
+                FrameKind::Context => unreachable!("context is not an annotation"),
+            };
+            crate::report::write_heading(heading, config, formatter)?;
+            for annotation in annotations {
+                write_eros_context(annotation, config, formatter)?;
+            }
+            writeln!(formatter, "\n---")?;
+        }
+        Ok(())
+    }
+    if !formatter.alternate() {
+        return write_compact_debug(
+            t,
+            formatter,
+            #[cfg(feature = "context")]
+            context,
+        );
+    }
+    crate::report::with_config(|config| {
+        #[cfg(feature = "location")]
+        crate::report::write_error_location(location, config, formatter)?;
+        #[cfg(feature = "anyhow")]
         {
-            writeln!(
-                formatter,
-                "{}:{}:{}\n\t- {}",
-                context.location.file(),
-                context.location.line(),
-                context.location.column(),
-                context.context
-            )
+            use crate::error_union::{AnyhowError, AnyhowErrorArc};
+            let anyhow_error: Option<&anyhow::Error> =
+                if let Some(err) = t.as_any().downcast_ref::<AnyhowError>() {
+                    Some(&err.0)
+                } else if let Some(err) = t.as_any().downcast_ref::<AnyhowErrorArc>() {
+                    Some(&*err.0)
+                } else {
+                    None
+                };
+            if let Some(anyhow_error) = anyhow_error {
+                // With `Caused by:`, the anyhow chain is rendered in causal order like `anyhow` does.
+                // Otherwise the root is the error and the rest of the chain is treated as context.
+                let mut chain = if config.caused_by() {
+                    write_error(anyhow_error, config, formatter)?;
+                    writeln!(formatter)?;
+                    crate::report::write_caused_by(
+                        anyhow_error.chain().skip(1),
+                        config,
+                        formatter,
+                    )?;
+                    None
+                } else {
+                    let mut chain = anyhow_error.chain().rev().peekable();
+                    let root = chain.next().unwrap();
+                    write_error(root, config, formatter)?;
+                    writeln!(formatter)?;
+                    Some(chain)
+                };
+                #[cfg(feature = "context")]
+                {
+                    let mut context_items = context
+                        .iter()
+                        .filter(|frame| frame.kind == FrameKind::Context)
+                        .peekable();
+                    let has_context = chain.as_mut().is_some_and(|chain| chain.peek().is_some())
+                        || context_items.peek().is_some();
+                    if has_context {
+                        crate::report::write_heading("Context", config, formatter)?;
+                    }
+                    for context_item in chain.into_iter().flatten() {
+                        write_context_item(context_item, config, formatter)?;
+                    }
+                    for context_item in context_items {
+                        write_eros_context(context_item, config, formatter)?;
+                    }
+                    if has_context {
+                        writeln!(formatter, "\n---")?;
+                    }
+                    write_annotations(context, config, formatter)?;
+                }
+                #[cfg(feature = "return_trace")]
+                write_return_trace(return_trace, config, formatter)?;
+                #[cfg(feature = "span_trace")]
+                write_span_trace(span_trace, config, formatter)?;
+                #[cfg(feature = "backtrace")]
+                {
+                    use std::backtrace::BacktraceStatus;
+
+                    let anyhow_backtrace = anyhow_error.backtrace();
+                    if matches!(anyhow_backtrace.status(), BacktraceStatus::Captured) {
+                        #[cfg(feature = "better_backtrace")]
+                        write_better_backtrace(anyhow_backtrace, config, formatter)?;
+                        #[cfg(not(feature = "better_backtrace"))]
+                        write_backtrace(anyhow_backtrace, config, formatter)?;
+                    } else if matches!(backtrace.status(), BacktraceStatus::Captured) {
+                        #[cfg(feature = "better_backtrace")]
+                        write_better_backtrace(backtrace, config, formatter)?;
+                        #[cfg(not(feature = "better_backtrace"))]
+                        write_backtrace(backtrace, config, formatter)?;
+                    }
+                }
+                return Ok(());
+            }
         }
-        #[cfg(not(feature = "location"))]
+        config
+            .theme()
+            .error()
+            .paint(config.color(), formatter, |f| fmt::Debug::fmt(&t, f))?;
+        writeln!(formatter, "\n---")?;
+        if config.caused_by() {
+            let causes = core::iter::successors(t.source(), |&cause| cause.source());
+            crate::report::write_caused_by(causes, config, formatter)?;
+        }
+        #[cfg(feature = "context")]
         {
-            writeln!(formatter, "\t- {}", context.context)
+            let mut context_items = context
+                .iter()
+                .filter(|frame| frame.kind == FrameKind::Context)
+                .peekable();
+            if context_items.peek().is_some() {
+                crate::report::write_heading("Context", config, formatter)?;
+                for context_item in context_items {
+                    write_eros_context(context_item, config, formatter)?;
+                }
+                writeln!(formatter, "\n---")?;
+            }
+            write_annotations(context, config, formatter)?;
         }
+        #[cfg(feature = "return_trace")]
+        write_return_trace(return_trace, config, formatter)?;
+        #[cfg(feature = "span_trace")]
+        write_span_trace(span_trace, config, formatter)?;
+        #[cfg(feature = "backtrace")]
+        {
+            use std::backtrace::BacktraceStatus;
+
+            if matches!(backtrace.status(), BacktraceStatus::Captured) {
+                #[cfg(feature = "better_backtrace")]
+                write_better_backtrace(backtrace, config, formatter)?;
+                #[cfg(not(feature = "better_backtrace"))]
+                write_backtrace(backtrace, config, formatter)?;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Writes the locations the error was propagated through, in order
+#[cfg(feature = "return_trace")]
+fn write_return_trace(
+    return_trace: &[&'static core::panic::Location<'static>],
+    config: &crate::ReportConfig,
+    formatter: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    if return_trace.is_empty() {
+        return Ok(());
     }
-    #[cfg(feature = "location")]
-    {
-        writeln!(
-            formatter,
-            "{}:{}:{}",
+    crate::report::write_heading("Return Trace", config, formatter)?;
+    for (index, location) in return_trace.iter().enumerate() {
+        write!(formatter, "\t{index}: ")?;
+        crate::report::write_source_location(
             location.file(),
             location.line(),
-            location.column()
+            Some(location.column()),
+            config,
+            formatter,
         )?;
+        writeln!(formatter)?;
     }
+    writeln!(formatter, "\n---")
+}
+
+/// Writes the spans that were active when the error was created, innermost first
+#[cfg(feature = "span_trace")]
+fn write_span_trace(
+    span_trace: &tracing_error::SpanTrace,
+    config: &crate::ReportConfig,
+    formatter: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    if span_trace.status() != tracing_error::SpanTraceStatus::CAPTURED {
+        return Ok(());
+    }
+    crate::report::write_heading("Span trace", config, formatter)?;
+    writeln!(formatter, "{span_trace}")?;
+    writeln!(formatter, "\n---")
+}
+
+/// The single line `{:?}` form, e.g. `ErrorUnion { error: .., context: [..] }`. The full report is `{:#?}`.
+fn write_compact_debug<T: SendSyncError + ?Sized>(
+    t: &T,
+    formatter: &mut fmt::Formatter<'_>,
+    #[cfg(feature = "context")] context: &[ContextFrame],
+) -> fmt::Result {
+    let mut debug = formatter.debug_struct("ErrorUnion");
     #[cfg(feature = "anyhow")]
     {
         use crate::error_union::{AnyhowError, AnyhowErrorArc};
-        let anyhow_error: Option<&anyhow::Error> =
-            if let Some(err) = t.as_any().downcast_ref::<AnyhowError>() {
-                Some(&err.0)
-            } else if let Some(err) = t.as_any().downcast_ref::<AnyhowErrorArc>() {
-                Some(&*err.0)
-            } else {
-                None
-            };
-        if let Some(anyhow_error) = anyhow_error {
-            let mut chain = anyhow_error.chain().rev().peekable();
-            let root = chain.next().unwrap();
-            writeln!(formatter, "{root}")?;
-            #[cfg(feature = "context")]
-            {
-                let has_context = chain.peek().is_some() || !context.is_empty();
-                if has_context {
-                    writeln!(formatter, "\nContext:")?;
-                }
-                for context_item in chain {
-                    writeln!(formatter, "\t- {}", context_item)?;
-                }
-                for context_item in context {
-                    write_eros_context(context_item, formatter)?;
-                }
-                if has_context {
-                    writeln!(formatter, "\n---")?;
-                }
-            }
-            #[cfg(feature = "backtrace")]
-            {
-                use std::backtrace::BacktraceStatus;
-
-                let anyhow_backtrace = anyhow_error.backtrace();
-                if matches!(anyhow_backtrace.status(), BacktraceStatus::Captured) {
-                    #[cfg(feature = "better_backtrace")]
-                    write_better_backtrace(anyhow_backtrace, formatter)?;
-                    #[cfg(not(feature = "better_backtrace"))]
-                    write_backtrace(anyhow_backtrace, formatter)?;
-                } else if matches!(backtrace.status(), BacktraceStatus::Captured) {
-                    #[cfg(feature = "better_backtrace")]
-                    write_better_backtrace(backtrace, formatter)?;
-                    #[cfg(not(feature = "better_backtrace"))]
-                    write_backtrace(backtrace, formatter)?;
-                }
-            }
-            return Ok(());
+        if let Some(err) = t.as_any().downcast_ref::<AnyhowError>() {
+            debug.field("error", &alloc::format!("{:#}", err.0));
+        } else if let Some(err) = t.as_any().downcast_ref::<AnyhowErrorArc>() {
+            debug.field("error", &alloc::format!("{:#}", err.0));
+        } else {
+            debug.field("error", &t);
         }
     }
-    fmt::Debug::fmt(&t, formatter)?;
-    writeln!(formatter, "\n---")?;
+    #[cfg(not(feature = "anyhow"))]
+    debug.field("error", &t);
     #[cfg(feature = "context")]
     {
-        if !context.is_empty() {
-            writeln!(formatter, "\nContext:")?;
-            for context_item in context.iter() {
-                write_eros_context(context_item, formatter)?;
-            }
-            writeln!(formatter, "\n---")?;
+        let frames = |annotations: bool| {
+            context
+                .iter()
+                .filter(move |frame| frame.kind.is_annotation() == annotations)
+        };
+        debug.field(
+            "context",
+            &DebugList(frames(false).map(|frame| alloc::format!("{}", frame.context))),
+        );
+        if frames(true).next().is_some() {
+            debug.field(
+                "annotations",
+                &DebugList(
+                    frames(true).map(|frame| alloc::format!("{}: {}", frame.kind, frame.context)),
+                ),
+            );
         }
     }
-    #[cfg(feature = "backtrace")]
-    {
-        use std::backtrace::BacktraceStatus;
+    debug.finish()
+}
 
-        if matches!(backtrace.status(), BacktraceStatus::Captured) {
-            #[cfg(feature = "better_backtrace")]
-            write_better_backtrace(backtrace, formatter)?;
-            #[cfg(not(feature = "better_backtrace"))]
-            write_backtrace(backtrace, formatter)?;
-        }
+#[cfg(feature = "context")]
+struct DebugList<I>(I);
+
+#[cfg(feature = "context")]
+impl<I> fmt::Debug for DebugList<I>
+where
+    I: Iterator + Clone,
+    I::Item: fmt::Debug,
+{
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.debug_list().entries(self.0.clone()).finish()
     }
-    Ok(())
+}
+
+/// Writes the error itself, an `anyhow::Error`, in the error style of the theme
+#[cfg(feature = "anyhow")]
+fn write_error(
+    error: &dyn fmt::Display,
+    config: &crate::ReportConfig,
+    formatter: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    config
+        .theme()
+        .error()
+        .paint(config.color(), formatter, |f| write!(f, "{error}"))
 }
 
 #[cfg(feature = "backtrace")]
 fn write_backtrace(
     backtrace: &std::backtrace::Backtrace,
+    config: &crate::ReportConfig,
     formatter: &mut fmt::Formatter<'_>,
 ) -> fmt::Result {
     use std::backtrace::BacktraceStatus;
 
     if matches!(backtrace.status(), BacktraceStatus::Captured) {
-        writeln!(formatter, "\nBacktrace:")?;
-        fmt::Display::fmt(&backtrace, formatter)?;
+        crate::report::write_heading("Backtrace", config, formatter)?;
+        crate::backtrace::write_filtered(backtrace, config, formatter)?;
     }
     Ok(())
 }
@@ -266,29 +509,98 @@ fn write_backtrace(
 #[cfg(feature = "better_backtrace")]
 fn write_better_backtrace(
     backtrace: &std::backtrace::Backtrace,
+    config: &crate::ReportConfig,
     formatter: &mut fmt::Formatter<'_>,
 ) -> fmt::Result {
-    let printer = color_backtrace::BacktracePrinter::new().add_frame_filter(Box::new(
-        |frames: &mut Vec<&color_backtrace::Frame>| {
-            frames.retain(|frame| {
-                !(frame.is_dependency_code()
-                    || frame.is_post_panic_code()
-                    || frame.is_runtime_init_code())
-            });
-        },
-    ));
+    let backtrace_config = config.backtrace();
+    let verbosity = if backtrace_config.source_snippets() {
+        color_backtrace::Verbosity::Full
+    } else {
+        color_backtrace::Verbosity::Medium
+    };
+    let mut colors = color_backtrace::ColorScheme::classic();
+    let theme = config.theme();
+    if !theme.heading().is_plain() {
+        colors.header = color_spec(theme.heading());
+    }
+    if !theme.backtrace().is_plain() {
+        colors.crate_code = color_spec(theme.backtrace());
+    }
+    let filter_config = backtrace_config.clone();
+    let printer = color_backtrace::BacktracePrinter::new()
+        .color_scheme(colors)
+        .lib_verbosity(verbosity)
+        .clear_frame_filters()
+        .add_frame_filter(Box::new(
+            move |frames: &mut Vec<&color_backtrace::Frame>| {
+                frames.retain(|frame| {
+                    filter_config.keeps_frame(
+                        frame.name.as_deref(),
+                        frame.filename.as_deref().and_then(|file| file.to_str()),
+                    )
+                });
+            },
+        ));
     let Ok(btparse_backtrace) = btparse::deserialize(backtrace) else {
-        write_backtrace(backtrace, formatter)?;
+        write_backtrace(backtrace, config, formatter)?;
         return Ok(());
     };
-    let Ok(backtrace_string) = printer.format_trace_to_string(&btparse_backtrace) else {
-        write_backtrace(backtrace, formatter)?;
+    let backtrace_string = if backtrace_config.color() {
+        printer.format_trace_to_string(&btparse_backtrace)
+    } else {
+        let mut out = color_backtrace::termcolor::NoColor::new(Vec::new());
+        printer
+            .print_trace(&btparse_backtrace, &mut out)
+            .map(|()| String::from_utf8_lossy(&out.into_inner()).into_owned())
+    };
+    let Ok(backtrace_string) = backtrace_string else {
+        write_backtrace(backtrace, config, formatter)?;
         return Ok(());
     };
-    fmt::Display::fmt(&backtrace_string, formatter)?;
+    if config.hyperlinks().is_none() || !config.color() {
+        return fmt::Display::fmt(&backtrace_string, formatter);
+    }
+    // The frame locations are written uncolored as `    at file:line`, so they can be linked
+    for line in backtrace_string.split_inclusive('\n') {
+        let location = line
+            .trim_end()
+            .strip_prefix("    at ")
+            .and_then(|location| location.rsplit_once(':'))
+            .and_then(|(file, line)| Some((file, line.parse().ok()?)));
+        match location {
+            Some((file, line)) => {
+                formatter.write_str("    at ")?;
+                crate::report::write_source_location(file, line, None, config, formatter)?;
+                writeln!(formatter)?;
+            }
+            None => formatter.write_str(line)?,
+        }
+    }
     Ok(())
 }
 
+/// The `color_backtrace` color of a theme style
+#[cfg(feature = "better_backtrace")]
+fn color_spec(style: crate::Style) -> color_backtrace::termcolor::ColorSpec {
+    use crate::Color;
+    use color_backtrace::termcolor::{Color as TermColor, ColorSpec};
+
+    let mut spec = ColorSpec::new();
+    spec.set_fg(style.fg_color().map(|color| match color {
+        Color::Black => TermColor::Black,
+        Color::Red => TermColor::Red,
+        Color::Green => TermColor::Green,
+        Color::Yellow => TermColor::Yellow,
+        Color::Blue => TermColor::Blue,
+        Color::Magenta => TermColor::Magenta,
+        Color::Cyan => TermColor::Cyan,
+        Color::White => TermColor::White,
+        Color::Fixed(color) => TermColor::Ansi256(color),
+    }));
+    spec.set_bold(style.is_bold());
+    spec
+}
+
 impl<Head, Tail> DebugFold for Cons<Head, Tail>
 where
     Cons<Head, Tail>: fmt::Debug,
@@ -298,9 +610,11 @@ where
     fn debug_fold(
         any: &dyn SendSyncError,
         formatter: &mut fmt::Formatter<'_>,
-        #[cfg(feature = "context")] context: &[ErosContext],
+        #[cfg(feature = "context")] context: &[ContextFrame],
         #[cfg(feature = "backtrace")] backtrace: &Backtrace,
         #[cfg(feature = "location")] location: &'static core::panic::Location<'static>,
+        #[cfg(feature = "return_trace")] return_trace: &[&'static core::panic::Location<'static>],
+        #[cfg(feature = "span_trace")] span_trace: &tracing_error::SpanTrace,
     ) -> fmt::Result {
         if let Some(head_ref) = (any as &dyn Any).downcast_ref::<Head>() {
             write_debug(
@@ -312,6 +626,10 @@ where
                 backtrace,
                 #[cfg(feature = "location")]
                 location,
+                #[cfg(feature = "return_trace")]
+                return_trace,
+                #[cfg(feature = "span_trace")]
+                span_trace,
             )
         } else {
             Tail::debug_fold(
@@ -323,6 +641,10 @@ where
                 backtrace,
                 #[cfg(feature = "location")]
                 location,
+                #[cfg(feature = "return_trace")]
+                return_trace,
+                #[cfg(feature = "span_trace")]
+                span_trace,
             )
         }
     }
@@ -1,16 +1,296 @@
-use crate::{ContextSource, ErrorUnion, type_set::TypeSet};
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::fmt;
+
+use crate::{
+    ContextFrame, ContextSource, ErrorUnion, FrameKind, SendSyncError, registry::Registry,
+    type_set::TypeSet,
+};
+
+/// A localizable user facing message. A message id plus named arguments, with optional default
+/// text used when no translation exists. Usually created with [`msg!`](crate::msg).
+///
+/// `UserMessage` can be used as user context or as a leaf error itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserMessage {
+    id: Cow<'static, str>,
+    default_text: Option<Cow<'static, str>>,
+    args: Vec<(Cow<'static, str>, String)>,
+}
+
+impl UserMessage {
+    pub fn new(id: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            id: id.into(),
+            default_text: None,
+            args: Vec::new(),
+        }
+    }
+
+    /// Sets the text used when no translation exists. `{name}` is replaced with the argument `name`.
+    pub fn with_default_text(mut self, default_text: impl Into<Cow<'static, str>>) -> Self {
+        self.default_text = Some(default_text.into());
+        self
+    }
+
+    /// Adds a named argument
+    pub fn with_arg(
+        mut self,
+        name: impl Into<Cow<'static, str>>,
+        value: impl fmt::Display,
+    ) -> Self {
+        self.args.push((name.into(), alloc::format!("{}", value)));
+        self
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn default_text(&self) -> Option<&str> {
+        self.default_text.as_deref()
+    }
+
+    pub fn args(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.args
+            .iter()
+            .map(|(name, value)| (&**name, value.as_str()))
+    }
+
+    /// Returns the value of the argument `name`
+    pub fn arg(&self, name: &str) -> Option<&str> {
+        self.args()
+            .find(|(arg_name, _)| *arg_name == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Renders through `translator`, falling back to the default text, then the id.
+    pub fn localize(&self, translator: &dyn Translator) -> String {
+        translator
+            .translate(self)
+            .unwrap_or_else(|| alloc::string::ToString::to_string(self))
+    }
+}
+
+impl fmt::Display for UserMessage {
+    /// Writes the default text with the arguments substituted, or the id if there is no default text
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(mut rest) = self.default_text.as_deref() else {
+            return f.write_str(&self.id);
+        };
+        while let Some(start) = rest.find('{') {
+            let (before, after) = rest.split_at(start);
+            f.write_str(before)?;
+            let value = after
+                .find('}')
+                .and_then(|end| Some((self.arg(&after[1..end])?, end)));
+            match value {
+                Some((value, end)) => {
+                    f.write_str(value)?;
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    f.write_str("{")?;
+                    rest = &after[1..];
+                }
+            }
+        }
+        f.write_str(rest)
+    }
+}
+
+impl core::error::Error for UserMessage {}
+
+impl From<UserMessage> for ContextSource {
+    fn from(message: UserMessage) -> Self {
+        ContextSource::Message(message)
+    }
+}
+
+/// Renders [`UserMessage`]s from a catalog, e.g. Fluent files.
+pub trait Translator {
+    /// Returns the translated message, or `None` if the catalog does not contain it.
+    fn translate(&self, message: &UserMessage) -> Option<String>;
+}
+
+impl<F> Translator for F
+where
+    F: Fn(&UserMessage) -> Option<String>,
+{
+    fn translate(&self, message: &UserMessage) -> Option<String> {
+        self(message)
+    }
+}
+
+impl ContextSource {
+    /// Renders this context, translating it if it is a [`UserMessage`]
+    pub fn localize(&self, translator: &dyn Translator) -> String {
+        match self {
+            ContextSource::Message(message) => message.localize(translator),
+            ContextSource::Sensitive(_) if crate::redact::should_redact() => {
+                String::from(crate::REDACTED)
+            }
+            ContextSource::Sensitive(context) => context.localize(translator),
+            other => alloc::string::ToString::to_string(other),
+        }
+    }
+}
+
+/// Implemented by errors to declare whether they are safe to show to users of an application.
+/// Errors are internal by default, only errors that return a message from [`UserFacing::user_message`]
+/// are shown. The message is found for an [`ErrorUnion`] once registered with
+/// [`register_user_facing`].
+///
+/// ```rust,ignore
+/// impl UserFacing for InvalidPasswordError {
+///     fn user_message(&self) -> Option<UserMessage> {
+///         Some(msg!("invalid-password", "Your password must be at least {min} characters long.", min = 8))
+///     }
+/// }
+///
+/// register_user_facing(InvalidPasswordError::user_message);
+/// ```
+pub trait UserFacing {
+    /// The message to show to users, or `None` if this error is internal.
+    fn user_message(&self) -> Option<UserMessage> {
+        None
+    }
+}
+
+impl UserFacing for UserMessage {
+    fn user_message(&self) -> Option<UserMessage> {
+        Some(self.clone())
+    }
+}
+
+impl<T> UserFacing for crate::Secret<T> {}
+
+macro_rules! impl_internal {
+    ($($ty:ty),* $(,)?) => {
+        $(impl UserFacing for $ty {})*
+    };
+}
+
+impl_internal!(
+    crate::StrError,
+    crate::AbsentValueError,
+    fmt::Error,
+    core::num::ParseIntError,
+    core::num::ParseFloatError,
+    core::num::TryFromIntError,
+    core::str::ParseBoolError,
+    core::str::Utf8Error,
+    core::char::ParseCharError,
+    core::array::TryFromSliceError,
+    alloc::string::FromUtf8Error,
+);
+
+#[cfg(feature = "std")]
+impl_internal!(std::io::Error);
+
+impl UserMessage {
+    /// The message used for errors that are not [`UserFacing`], with the id `internal-error`
+    pub fn internal_error() -> Self {
+        UserMessage::new("internal-error").with_default_text("An internal error occurred.")
+    }
+}
+
+static USER_MESSAGES: Registry<Option<UserMessage>> = Registry::new();
+
+/// Sets the message shown to users for errors of type `T`, see [`ErrorUnion::user_report`].
+/// Errors of a type without a registered message are internal, except a [`UserMessage`] leaf
+/// error, which is its own message. Registering again replaces the previous function. Since
+/// `user_message` is a function, this also works for foreign types, which cannot implement
+/// [`UserFacing`].
+///
+/// ```rust,ignore
+/// register_user_facing(InvalidPasswordError::user_message);
+/// register_user_facing(|error: &std::io::Error| match error.kind() {
+///     std::io::ErrorKind::StorageFull => Some(msg!("disk-full", "The disk is full.")),
+///     _ => None,
+/// });
+/// ```
+pub fn register_user_facing<T: SendSyncError>(user_message: fn(&T) -> Option<UserMessage>) {
+    USER_MESSAGES.register(user_message);
+}
 
 impl<E> ErrorUnion<E>
 where
     E: TypeSet,
 {
+    /// The message registered for the type of the inner error with [`register_user_facing`], the
+    /// inner error itself if it is a [`UserMessage`], or `None` if it is internal
+    pub fn user_message(&self) -> Option<UserMessage> {
+        let any = self.inner.error.as_any();
+        match any.downcast_ref::<UserMessage>() {
+            Some(message) => Some(message.clone()),
+            None => USER_MESSAGES.call(any).flatten(),
+        }
+    }
+
+    /// A report safe to show to users. The inner error's user message, or a generic message
+    /// for internal errors (see [`UserMessage::internal_error`]), followed by each user context on its own line,
+    /// then each user facing annotation grouped by kind, e.g. `help: run foo init first`.
+    pub fn user_report(&self) -> String {
+        let mut report = alloc::string::ToString::to_string(
+            &self
+                .user_message()
+                .unwrap_or_else(UserMessage::internal_error),
+        );
+        for context in self.user_contexts() {
+            report.push('\n');
+            report.push_str(&alloc::string::ToString::to_string(context));
+        }
+        for frame in self.user_annotations() {
+            report.push_str(&alloc::format!("\n{}: {}", frame.kind, frame.context));
+        }
+        report
+    }
+
+    /// Same as [`ErrorUnion::user_report`], but rendered through `translator`
+    pub fn localized_user_report(&self, translator: &dyn Translator) -> String {
+        let mut report = self
+            .user_message()
+            .unwrap_or_else(UserMessage::internal_error)
+            .localize(translator);
+        for context in self.localized_user_contexts(translator) {
+            report.push('\n');
+            report.push_str(&context);
+        }
+        for frame in self.user_annotations() {
+            report.push_str(&alloc::format!(
+                "\n{}: {}",
+                frame.kind,
+                frame.context.localize(translator)
+            ));
+        }
+        report
+    }
+
     pub fn user_contexts(&self) -> impl Iterator<Item = &ContextSource> {
         self.inner
             .context
             .iter()
-            .filter(|e| e.is_user_facing)
+            .filter(|e| e.is_user_facing && e.kind == FrameKind::Context)
             .map(|e| &e.context)
     }
+
+    /// The user facing annotations, grouped by kind in the order of [`FrameKind::ANNOTATIONS`]
+    fn user_annotations(&self) -> impl Iterator<Item = &ContextFrame> {
+        FrameKind::ANNOTATIONS.into_iter().flat_map(move |kind| {
+            self.frames()
+                .filter(move |frame| frame.kind == kind && frame.is_user_facing)
+        })
+    }
+
+    /// Renders the user facing context through `translator`. Context that is not a
+    /// [`UserMessage`] or has no translation falls back to its default text.
+    pub fn localized_user_contexts<'a>(
+        &'a self,
+        translator: &'a dyn Translator,
+    ) -> impl Iterator<Item = String> + 'a {
+        self.user_contexts()
+            .map(move |context| context.localize(translator))
+    }
 }
 
 #[cfg(test)]
@@ -77,4 +357,208 @@ mod tests {
             Some("Your password must be at least 8 characters long.")
         );
     }
+
+    fn german(message: &UserMessage) -> Option<String> {
+        match message.id() {
+            "disk-full" => Some(format!(
+                "Die Festplatte ist voll: {}",
+                message.arg("path").unwrap_or_default()
+            )),
+            "try-again" => Some("Bitte versuchen Sie es erneut.".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn msg_macro_captures_id_default_text_and_args() {
+        let path = "/data";
+        let message = crate::msg!("disk-full", "The disk is full at {path}", path = path);
+        assert_eq!(message.id(), "disk-full");
+        assert_eq!(message.default_text(), Some("The disk is full at {path}"));
+        assert_eq!(message.args().collect::<Vec<_>>(), vec![("path", "/data")]);
+        assert_eq!(message.to_string(), "The disk is full at /data");
+
+        let message = crate::msg!("disk-full", path = path, free = 0);
+        assert_eq!(message.arg("free"), Some("0"));
+        assert_eq!(message.to_string(), "disk-full");
+    }
+
+    #[test]
+    fn default_text_leaves_unknown_placeholders() {
+        let message = crate::msg!("x", "{missing} and {name", name = 1);
+        assert_eq!(message.to_string(), "{missing} and {name");
+    }
+
+    #[test]
+    fn localized_user_contexts_translate_with_fallback() {
+        let union: ErrorUnion<(SystemDiskError,)> = ErrorUnion::new(SystemDiskError);
+        let union = union
+            .context("Normal context")
+            .user_context(crate::msg!(
+                "disk-full",
+                "The disk is full at {path}",
+                path = "/data"
+            ))
+            .user_context(crate::msg!("unknown-id", "Something went wrong"))
+            .user_context("Plain user context");
+
+        let localized = union.localized_user_contexts(&german).collect::<Vec<_>>();
+        assert_eq!(
+            localized,
+            vec![
+                "Die Festplatte ist voll: /data",
+                "Something went wrong",
+                "Plain user context"
+            ]
+        );
+
+        let defaults = union
+            .user_contexts()
+            .map(|c| c.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(defaults[0], "The disk is full at /data");
+    }
+
+    #[test]
+    fn leaf_error_can_be_a_user_message() {
+        let union: ErrorUnion<(UserMessage,)> =
+            ErrorUnion::new(crate::msg!("try-again", "Please try again."));
+        let union = union.user_context(crate::msg!("disk-full", path = "/tmp"));
+
+        let leaf = union
+            .inner_ref()
+            .as_any()
+            .downcast_ref::<UserMessage>()
+            .unwrap();
+        assert_eq!(leaf.localize(&german), "Bitte versuchen Sie es erneut.");
+        assert_eq!(union.to_string(), "Please try again.");
+        assert_eq!(
+            union.localized_user_contexts(&german).collect::<Vec<_>>(),
+            vec!["Die Festplatte ist voll: /tmp"]
+        );
+    }
+
+    impl UserFacing for SystemDiskError {}
+
+    impl UserFacing for InvalidPasswordError {
+        fn user_message(&self) -> Option<UserMessage> {
+            Some(crate::msg!(
+                "invalid-password",
+                "Your password must be at least {min} characters long.",
+                min = 8
+            ))
+        }
+    }
+
+    #[test]
+    fn user_message_finds_active_variant() {
+        register_user_facing(InvalidPasswordError::user_message);
+        let union: ErrorUnion<(SystemDiskError, InvalidPasswordError)> =
+            ErrorUnion::new(InvalidPasswordError);
+        assert_eq!(
+            union.user_message().map(|m| m.to_string()).as_deref(),
+            Some("Your password must be at least 8 characters long.")
+        );
+
+        let union: ErrorUnion<(SystemDiskError, InvalidPasswordError)> =
+            ErrorUnion::new(SystemDiskError);
+        assert_eq!(union.user_message(), None);
+    }
+
+    #[test]
+    fn user_report_combines_message_and_user_contexts() {
+        register_user_facing(InvalidPasswordError::user_message);
+        let union: ErrorUnion<(SystemDiskError, InvalidPasswordError)> =
+            ErrorUnion::new(InvalidPasswordError);
+        let union = union
+            .context("Password validation failed")
+            .user_context("Please choose a stronger password.");
+        assert_eq!(
+            union.user_report(),
+            "Your password must be at least 8 characters long.\nPlease choose a stronger password."
+        );
+    }
+
+    #[test]
+    fn user_report_falls_back_for_internal_errors() {
+        let union: ErrorUnion<(SystemDiskError, InvalidPasswordError)> =
+            ErrorUnion::new(SystemDiskError);
+        let union = union.context("Failed to read /etc/my-app/config.toml");
+        assert_eq!(union.user_report(), "An internal error occurred.");
+
+        let union: ErrorUnion<(std::io::Error,)> =
+            ErrorUnion::new(std::io::Error::other("disk gone"));
+        assert_eq!(union.user_report(), "An internal error occurred.");
+    }
+
+    #[test]
+    fn any_error_union_finds_user_messages() {
+        let union: ErrorUnion = ErrorUnion::new(crate::msg!("try-again", "Please try again."));
+        assert_eq!(union.user_report(), "Please try again.");
+
+        register_user_facing(InvalidPasswordError::user_message);
+        let union: ErrorUnion = ErrorUnion::new(InvalidPasswordError);
+        assert_eq!(
+            union.user_report(),
+            "Your password must be at least 8 characters long."
+        );
+
+        let union: ErrorUnion = ErrorUnion::new(SystemDiskError);
+        assert_eq!(union.user_report(), "An internal error occurred.");
+    }
+
+    #[derive(Debug)]
+    struct PaymentDeclinedError;
+    impl std::fmt::Display for PaymentDeclinedError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "card declined by issuer")
+        }
+    }
+    impl std::error::Error for PaymentDeclinedError {}
+
+    #[test]
+    fn foreign_errors_can_be_registered() {
+        let union: ErrorUnion<(std::fmt::Error, PaymentDeclinedError)> =
+            ErrorUnion::new(std::fmt::Error);
+        assert_eq!(union.user_report(), "An internal error occurred.");
+
+        register_user_facing(|_: &PaymentDeclinedError| {
+            Some(crate::msg!(
+                "payment-declined",
+                "Your payment was declined."
+            ))
+        });
+        let union: ErrorUnion<(std::fmt::Error, PaymentDeclinedError)> =
+            ErrorUnion::new(PaymentDeclinedError);
+        assert_eq!(union.user_report(), "Your payment was declined.");
+    }
+
+    #[test]
+    fn localized_user_report() {
+        let union: ErrorUnion<(SystemDiskError,)> = ErrorUnion::new(SystemDiskError);
+        let union = union.user_context(crate::msg!("try-again", "Please try again."));
+        assert_eq!(
+            union.localized_user_report(&german),
+            "An internal error occurred.\nBitte versuchen Sie es erneut."
+        );
+    }
+
+    #[test]
+    fn user_report_includes_user_facing_annotations() {
+        register_user_facing(InvalidPasswordError::user_message);
+        let union: ErrorUnion<(InvalidPasswordError,)> = ErrorUnion::new(InvalidPasswordError);
+        let union = union
+            .context("Password validation failed")
+            .user_annotate(FrameKind::Help, "Use a password manager")
+            .user_context("Please choose a stronger password.")
+            .note("retrying shard 3 on db-7")
+            .user_annotate(
+                FrameKind::Warning,
+                "Your account will be locked after 3 attempts",
+            );
+        assert_eq!(
+            union.user_report(),
+            "Your password must be at least 8 characters long.\nPlease choose a stronger password.\nwarning: Your account will be locked after 3 attempts\nhelp: Use a password manager"
+        );
+    }
 }
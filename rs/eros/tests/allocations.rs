@@ -0,0 +1,93 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt;
+
+use eros::ErrorUnion;
+
+/// Counts the allocations made on the current thread, so tests running in parallel do not interfere
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// The number of new allocations made by `f`. Reallocations are not counted.
+fn allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+#[derive(Debug)]
+struct RequestError(String);
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "request failed: {}", self.0)
+    }
+}
+
+impl std::error::Error for RequestError {}
+
+#[derive(Debug)]
+struct ByteError(u8);
+
+impl fmt::Display for ByteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "byte error {}", self.0)
+    }
+}
+
+impl std::error::Error for ByteError {}
+
+#[test]
+fn into_inner_reuses_the_allocation() {
+    let union: ErrorUnion<(RequestError,)> = ErrorUnion::new(RequestError("timeout".into()));
+    #[cfg(feature = "context")]
+    let union = union.context("fetching users");
+
+    let (error, count) = allocations(|| union.into_inner());
+    assert_eq!(count, 0);
+    assert_eq!(error.to_string(), "request failed: timeout");
+    assert!((&*error as &dyn std::any::Any).is::<RequestError>());
+}
+
+#[test]
+fn into_inner_of_small_alignment_error_allocates() {
+    let union: ErrorUnion<(ByteError,)> = ErrorUnion::new(ByteError(1));
+    let (error, count) = allocations(|| union.into_inner());
+    assert_eq!(count, 1);
+    assert!((&*error as &dyn std::any::Any).is::<ByteError>());
+}
+
+#[cfg(feature = "context")]
+#[test]
+fn first_context_frames_do_not_allocate() {
+    let union: ErrorUnion<(RequestError,)> = ErrorUnion::new(RequestError("timeout".into()));
+    let (union, count) = allocations(|| {
+        union
+            .context("fetching users")
+            .context("loading dashboard")
+            .context("handling request")
+    });
+    assert_eq!(count, 0);
+    assert_eq!(union.frames().count(), 3);
+}
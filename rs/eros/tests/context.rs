@@ -1,7 +1,8 @@
 #![cfg(all(feature = "context", feature = "backtrace"))]
 
 use eros::{
-    AbsentValueError, AnyError, Context, ErrorUnion, IntoDynUnion, IntoUnion, ReshapeUnion, error,
+    AbsentValueError, AnyError, Context, ErrorUnion, FrameKind, IntoDynUnion, IntoUnion,
+    ReshapeUnion, error,
 };
 
 #[test]
@@ -49,7 +50,7 @@ fn error_union() {
     > = map_widen();
     assert!(result.is_err());
     let error = result.unwrap_err();
-    let message = format!("{:?}", error);
+    let message = format!("{:#?}", error);
     assert!(
         message.contains("Context:"),
         "Expected context in message:\n{}",
@@ -63,7 +64,7 @@ fn error_union() {
     );
     let result: Result<(), ErrorUnion<(std::io::Error, std::sync::mpsc::RecvError)>> = narrow();
     assert!(result.is_err());
-    let message = format!("{:?}", result.unwrap_err());
+    let message = format!("{:#?}", result.unwrap_err());
     assert!(
         message.contains("Context:"),
         "Expected context in message:\n{}",
@@ -71,7 +72,7 @@ fn error_union() {
     );
     let result: Result<(), ErrorUnion<AnyError>> = traced_macro();
     assert!(result.is_err());
-    let message = format!("{:?}", result.unwrap_err());
+    let message = format!("{:#?}", result.unwrap_err());
     assert!(
         message.contains("Context:"),
         "Expected context in message:\n{}",
@@ -133,7 +134,7 @@ fn bail() {
     let result: Result<(), ErrorUnion> = func2();
     println!("{:?}", result.as_ref().unwrap_err());
     assert!(result.is_err());
-    let message = format!("{:?}", result.unwrap_err());
+    let message = format!("{:#?}", result.unwrap_err());
     assert!(
         message.contains("Context:"),
         "Expected context in message:\n{}",
@@ -155,7 +156,7 @@ fn ensure() {
     let result: Result<(), ErrorUnion> = func2();
     println!("{:?}", result.as_ref().unwrap_err());
     assert!(result.is_err());
-    let message = format!("{:?}", result.unwrap_err());
+    let message = format!("{:#?}", result.unwrap_err());
     assert!(
         message.contains("Context:"),
         "Expected context in message:\n{}",
@@ -193,13 +194,59 @@ fn nesting_traced_dyn_calls() {
     }
 
     let result: eros::Result<()> = func2();
-    let message = format!("{:?}", result.unwrap_err());
+    let message = format!("{:#?}", result.unwrap_err());
 
     let count = message.match_indices("Context:").count();
     assert_eq!(count, 1, "Expected only one 'Context:', got:\n{}", message);
     println!("{}", message);
 }
 
+#[test]
+fn annotations() {
+    fn read_config() -> eros::Result<(), (std::io::Error,)> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "config.toml not found",
+        ))
+        .context("Reading config")
+        .note("The config is read from the current directory")
+        .help("Run `foo init` first")
+        .warning("The cache may be stale")
+    }
+
+    let error = read_config().unwrap_err();
+    let message = format!("{:#?}", error);
+    assert!(
+        message.contains("Context:\n") && message.contains("\t- Reading config"),
+        "Expected context in message:\n{}",
+        message
+    );
+    let context_section = message.split("Context:").nth(1).unwrap();
+    let context_section = context_section.split("---").next().unwrap();
+    assert!(
+        !context_section.contains("foo init"),
+        "Expected annotations outside of context:\n{}",
+        message
+    );
+    let warning = message.find("Warning:\n").unwrap();
+    let note = message.find("Note:\n").unwrap();
+    let help = message.find("Help:\n").unwrap();
+    assert!(warning < note && note < help, "{}", message);
+    assert!(message[help..].contains("\t- Run `foo init` first"));
+
+    let helps = error
+        .annotations(FrameKind::Help)
+        .map(|help| help.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(helps, vec!["Run `foo init` first"]);
+    assert_eq!(error.annotations(FrameKind::Context).count(), 1);
+
+    let none: Option<()> = None;
+    let error = none.help("Pass `--config`").unwrap_err();
+    assert!(format!("{:#?}", error).contains("Help:\n"));
+    assert!(!format!("{:#?}", error).contains("\nContext:\n"), "{:#?}", error);
+}
+
 #[cfg(all(feature = "anyhow", not(feature = "location")))]
 #[test]
 fn integration_with_anyhow() {
@@ -235,8 +282,8 @@ fn integration_with_anyhow() {
     let result = eros_result().context("eros context");
     let error = result.as_ref().unwrap_err();
 
-    panic!("{:?}", error);
-    let message = format!("{:?}", error);
+    panic!("{:#?}", error);
+    let message = format!("{:#?}", error);
     assert!(
         message.contains("This is the root"),
         "Expected root error in message:\n{}",
@@ -0,0 +1,33 @@
+#![cfg(feature = "scope")]
+
+use std::sync::Mutex;
+
+use eros::{ErrorUnion, ScopeStack, ScopeStorage, StrError};
+
+/// A single stack shared by every thread, like a storage on a single core `no_std` target
+struct GlobalStorage(Mutex<ScopeStack>);
+
+impl ScopeStorage for GlobalStorage {
+    fn with_stack(&self, f: &mut dyn FnMut(&mut ScopeStack)) {
+        f(&mut self.0.lock().unwrap())
+    }
+}
+
+static STORAGE: GlobalStorage = GlobalStorage(Mutex::new(ScopeStack::new()));
+
+#[test]
+fn scopes_use_the_set_storage() {
+    eros::set_scope_storage(&STORAGE);
+
+    let error = eros::scope("syncing", || {
+        assert_eq!(STORAGE.0.lock().unwrap().len(), 1);
+        let error: ErrorUnion<(StrError,)> =
+            std::thread::spawn(|| ErrorUnion::new(StrError::from("offline")))
+                .join()
+                .unwrap();
+        error
+    });
+    let contexts: Vec<_> = error.frames().map(|f| f.context().to_string()).collect();
+    assert_eq!(contexts, ["syncing"]);
+    assert!(STORAGE.0.lock().unwrap().is_empty());
+}
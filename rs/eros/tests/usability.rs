@@ -245,14 +245,14 @@ impl std::error::Error for MyErrorType {
 fn map_inner() {
     let error: ErrorUnion<(std::io::Error,)> = ErrorUnion::new(std::io::Error::other("wuaaaaahhh"));
     let error: ErrorUnion<(IoErrorWrapper,)> = error.map(IoErrorWrapper);
-    let message = format!("{:?}", error);
+    let message = format!("{:#?}", error);
     assert!(
         !message.contains("Context:"),
         "Expected no context in message:\n{}",
         message
     );
     let error: ErrorUnion<(MyErrorType,)> = error.map(|e| MyErrorType(Box::new(e.0)));
-    let message = format!("{:?}", error);
+    let message = format!("{:#?}", error);
     assert!(
         !message.contains("Context:"),
         "Expected no context in message:\n{}",
@@ -260,7 +260,7 @@ fn map_inner() {
     );
     let error: ErrorUnion<(std::io::Error,)> = ErrorUnion::new(std::io::Error::other("io error"));
     let error: ErrorUnion<(MyErrorType,)> = error.map(|e| MyErrorType(Box::new(e)));
-    let message = format!("{:?}", error);
+    let message = format!("{:#?}", error);
     assert!(
         !message.contains("Context:"),
         "Expected no context in message:\n{}",